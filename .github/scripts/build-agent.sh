@@ -3,7 +3,11 @@ agent="calzone-display-agent"
 src="target/release"
 dst="calzone_display/.bins"
 
-cargo build -p ${agent} --release
+case "$(uname -s)" in
+    CYGWIN*|MINGW*|MSYS*) agent="${agent}.exe" ;;
+esac
+
+cargo build -p calzone-display-agent --release
 mkdir -p ${dst}
 mv ${src}/${agent} ${dst}
 cargo clean
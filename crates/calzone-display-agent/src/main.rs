@@ -1,35 +1,66 @@
+use data::geometry::{Camera, MeshFileInfo};
+use data::ipc::{Packet, Token};
+use data::session::Player;
+use data::socket::{Address, Listener, Receiver};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use std::env;
-
-use data::ipc::Token;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 
 fn main() -> Result<(), u8> {
-    let oss = env::args().nth(1)
-        .expect("missing OSS name");
+    let arg = env::args().nth(1)
+        .expect("missing OSS name, --listen, --open or --replay");
 
-    let (tx, rx): (IpcSender<Token>, IpcReceiver<Token>) = ipc::channel().unwrap();
-    let oss = IpcSender::connect(oss).unwrap();
-    oss.send(tx).unwrap();
-    let receiver = std::thread::spawn(move || loop {
-        match rx.try_recv() {
-            Ok(data) => match data {
-                Token::Close => display::geometry::set_close(),
-                Token::Events(events) => display::event::set(events),
-                Token::Geometry(data) => display::geometry::set_data(data),
-                Token::Stop => {
-                    display::app::set_exit();
-                    break
-                },
-                Token::Stl(path) => display::geometry::set_stl(path),
-            },
-            Err(_) => {
-                std::thread::sleep(std::time::Duration::from_millis(1));
-            }
-        }
-    });
+    let receiver = match arg.as_str() {
+        "--open" => {
+            let path = env::args().nth(2)
+                .expect("missing geometry file");
+            Some(std::thread::spawn(move || open(path)))
+        },
+        "--listen" => {
+            let address: Address = env::args().nth(2)
+                .expect("missing listen address")
+                .parse()
+                .unwrap_or_else(|err| panic!("{}", err));
+            let listener = Listener::bind(&address)
+                .unwrap_or_else(|err| panic!("could not listen at {:?} ({})", address, err));
+            // The listener blocks waiting for clients, thus it is not joined on exit.
+            std::thread::spawn(move || listen(listener));
+            None
+        },
+        "--replay" => {
+            let path = env::args().nth(2)
+                .expect("missing session file");
+            let player = Player::open(path.as_str())
+                .unwrap_or_else(|err| panic!("could not open '{}' ({})", path, err));
+            Some(std::thread::spawn(move || replay(player)))
+        },
+        _ => {
+            let (tx, rx): (IpcSender<Packet>, IpcReceiver<Packet>) = ipc::channel().unwrap();
+            let (camera_tx, camera_rx): (IpcSender<Camera>, IpcReceiver<Camera>) =
+                ipc::channel().unwrap();
+            let oss = IpcSender::connect(arg).unwrap();
+            oss.send((tx, camera_rx)).unwrap();
+            display::app::on_camera(move |camera| {
+                let _ = camera_tx.send(camera); // Ignore a gone client.
+            });
+            Some(std::thread::spawn(move || loop {
+                match rx.try_recv() {
+                    Ok(Packet::Token(token)) => if !handle(token) {
+                        break
+                    },
+                    Ok(Packet::Events(events)) => display::event::set_columns(events.into()),
+                    Err(_) => {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                }
+            }))
+        },
+    };
     let rc = display::app::run();
-    if let Err(err) = receiver.join() {
+    if let Some(receiver) = receiver && let Err(err) = receiver.join() {
         std::panic::resume_unwind(err);
     }
     match rc {
@@ -37,3 +68,98 @@ fn main() -> Result<(), u8> {
         rc => Err(rc),
     }
 }
+
+/// Forward a token to the display. Returns `false` once the agent should stop.
+fn handle(token: Token) -> bool {
+    match token {
+        Token::Assembly(assembly) => display::geometry::set_assembly(assembly),
+        Token::Camera(camera) => display::app::set_camera(camera),
+        Token::Clipping(clipping) => display::geometry::set_clipping(clipping),
+        Token::Close => display::geometry::set_close(),
+        Token::Events(columns) => display::event::set_columns(columns),
+        Token::Export(path) => display::export::set_export(path),
+        Token::Filter(filter) => display::event::set_filter(filter),
+        Token::Geometry(data) => display::geometry::set_data(data),
+        Token::Mesh(mesh) => display::geometry::set_mesh(mesh),
+        Token::Overlay(overlay) => display::event::set_overlay(overlay),
+        Token::Stop => {
+            display::app::set_exit();
+            return false
+        },
+        Token::Voxels(voxels) => display::event::set_voxels(voxels),
+    }
+    true
+}
+
+/// Display a geometry file, without any Python client.
+fn open(path: String) {
+    let extension = Path::new(path.as_str())
+        .extension()
+        .and_then(OsStr::to_str);
+    match extension {
+        Some("gdml") => match data::gdml::load(path.as_str()) {
+            Ok(data) => display::geometry::set_data(data),
+            Err(err) => eprintln!("calzone-display-agent: could not load '{}' ({})", path, err),
+        },
+        Some("glb") | Some("gltf") | Some("obj") | Some("ply") | Some("stl") => {
+            let settings = display::geometry::LoadSettings::default();
+            display::geometry::set_mesh(MeshFileInfo {
+                path,
+                unit: 1.0,
+                compute_normal: settings.compute_normal,
+                interpolate_normal: settings.interpolate_normal,
+            })
+        },
+        _ => eprintln!(
+            "calzone-display-agent: bad geometry file (expected a GDML or mesh file, found '{}')",
+            path,
+        ),
+    }
+}
+
+/// Serve remote clients, one at a time, until one of them requests a stop.
+fn listen(listener: Listener) {
+    loop {
+        let stream = match listener.accept() {
+            Ok(stream) => stream,
+            Err(err) => {
+                eprintln!("calzone-display-agent: {}", err);
+                continue
+            },
+        };
+        let mut receiver = Receiver::new(stream);
+        loop {
+            match receiver.recv() {
+                Ok(Some(token)) => if !handle(token) {
+                    return
+                },
+                Ok(None) => break, // The client disconnected.
+                Err(err) => {
+                    eprintln!("calzone-display-agent: {}", err);
+                    break
+                },
+            }
+        }
+    }
+}
+
+/// Replay a recorded session, respecting its timing. The display is left open afterwards.
+fn replay(player: Player) {
+    let start = Instant::now();
+    for record in player {
+        let (time, token) = match record {
+            Ok(record) => record,
+            Err(err) => {
+                eprintln!("calzone-display-agent: {}", err);
+                break
+            },
+        };
+        let delay = Duration::from_secs_f64(time.max(0.0));
+        if let Some(delay) = delay.checked_sub(start.elapsed()) {
+            std::thread::sleep(delay);
+        }
+        if !handle(token) {
+            break
+        }
+    }
+}
@@ -1,51 +1,246 @@
+use data::geometry::Camera;
+use data::ipc::Token;
+use data::session::Recorder;
 use pyo3::prelude::*;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::sync::GILOnceCell;
+use std::sync::Mutex;
 
 #[cfg(feature = "thread")]
-use std::{sync::Mutex, thread};
+use std::thread;
+
+#[cfg(not(any(feature = "ipc", feature = "thread")))]
+compile_error!("at least one of the 'ipc' or 'thread' features must be enabled");
+
+
+// ===============================================================================================
+//
+// Display backends.
+//
+// ===============================================================================================
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    /// The display runs in a separate process (calzone-display-agent).
+    #[cfg(feature = "ipc")]
+    Process,
+    /// The display runs in a thread of the Python process.
+    #[cfg(feature = "thread")]
+    Thread,
+}
+
+static BACKEND: GILOnceCell<Backend> = GILOnceCell::new();
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// The last camera reported by the display.
+static CAMERA: Mutex<Option<Camera>> = Mutex::new(None);
 
 #[cfg(feature = "thread")]
 static HANDLE: Mutex<Option<thread::JoinHandle<u8>>> = Mutex::new(None);
 
-pub fn spawn(module: &Bound<PyModule>) -> PyResult<()> {
-    #[cfg(feature = "ipc")]
-    crate::ipc::spawn_agent(module.py())?;
+impl Backend {
+    fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "ipc")]
+            Self::Process => "process",
+            #[cfg(feature = "thread")]
+            Self::Thread => "thread",
+        }
+    }
 
-    #[cfg(feature = "thread")]
-    {
-        let handle = thread::spawn(display::app::run);
-        HANDLE
-            .lock()
-            .unwrap()
-            .replace(handle);
+    fn start(&self, _py: Python<'_>) -> PyResult<()> {
+        match self {
+            #[cfg(feature = "ipc")]
+            Self::Process => crate::ipc::spawn_agent(_py),
+            #[cfg(feature = "thread")]
+            Self::Thread => {
+                display::app::on_camera(report_camera);
+                let handle = thread::spawn(display::app::run);
+                HANDLE
+                    .lock()
+                    .unwrap()
+                    .replace(handle);
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Default for Backend {
+    #[cfg(all(feature = "ipc", feature = "thread"))]
+    fn default() -> Self {
+        // Winit must run on the main thread under MacOS, thus in a separate process.
+        if cfg!(target_os = "macos") {
+            Self::Process
+        } else {
+            Self::Thread
+        }
+    }
+
+    #[cfg(not(all(feature = "ipc", feature = "thread")))]
+    fn default() -> Self {
+        #[cfg(feature = "ipc")]
+        return Self::Process;
+
+        #[cfg(feature = "thread")]
+        return Self::Thread;
+    }
+}
+
+impl<'py> FromPyObject<'py> for Backend {
+    fn extract_bound(ob: &Bound<'py, PyAny>) -> PyResult<Self> {
+        const AVAILABLE: &[&str] = &[
+            #[cfg(feature = "ipc")]
+            "'process'",
+            #[cfg(feature = "thread")]
+            "'thread'",
+        ];
+
+        let name: String = ob.extract()?;
+        match name.as_str() {
+            #[cfg(feature = "ipc")]
+            "process" => Ok(Self::Process),
+            #[cfg(feature = "thread")]
+            "thread" => Ok(Self::Thread),
+            _ => Err(PyValueError::new_err(format!(
+                "bad backend (expected {}, found '{}')",
+                AVAILABLE.join(" or "),
+                name,
+            ))),
+        }
+    }
+}
+
+/// Return the running backend, starting the default one if none.
+fn get(py: Python<'_>) -> PyResult<Backend> {
+    BACKEND
+        .get_or_try_init(py, || {
+            let backend = Backend::default();
+            backend.start(py)?;
+            Ok::<_, PyErr>(backend)
+        })
+        .copied()
+}
+
+pub fn configure(py: Python<'_>, backend: Backend) -> PyResult<()> {
+    match BACKEND.get(py) {
+        Some(current) if *current == backend => Ok(()),
+        Some(current) => Err(PyRuntimeError::new_err(format!(
+            "bad backend (the '{}' backend is already running)",
+            current.name(),
+        ))),
+        None => {
+            backend.start(py)?;
+            let _ = BACKEND.set(py, backend);
+            Ok(())
+        },
     }
+}
 
+pub fn initialise(module: &Bound<PyModule>) -> PyResult<()> {
     let stopper = wrap_pyfunction!(stop, module)?;
     module.py().import_bound("atexit")?
       .call_method1("register", (stopper,))?;
+    Ok(())
+}
 
+/// Record all subsequent tokens to a session file, or stop recording if `None`. The display
+/// camera is recorded as well, when recording starts and whenever the display reports a change.
+/// Note that the camera of remote agents (socket) is not recorded.
+pub fn record(path: Option<&str>) -> PyResult<()> {
+    let recorder = match path {
+        Some(path) => {
+            let mut recorder = Recorder::create(path)
+                .map_err(|err| PyRuntimeError::new_err(format!(
+                    "could not create session file '{}' ({})",
+                    path,
+                    err,
+                )))?;
+            let camera = *CAMERA.lock().unwrap();
+            if let Some(camera) = camera {
+                recorder.record(&Token::Camera(camera))
+                    .map_err(|err| PyRuntimeError::new_err(format!(
+                        "could not record display session ({})",
+                        err,
+                    )))?;
+            }
+            Some(recorder)
+        },
+        None => None,
+    };
+    *RECORDER.lock().unwrap() = recorder;
     Ok(())
 }
 
-#[cfg(feature = "ipc")]
-#[pyfunction]
-fn stop(py: Python<'_>) -> PyResult<()> {
-    crate::ipc::send_stop(py)
+/// Record a display camera change, if recording. This is called by the display, thus errors
+/// cannot be reported and are ignored.
+pub(crate) fn report_camera(camera: Camera) {
+    *CAMERA.lock().unwrap() = Some(camera);
+    if let Some(recorder) = RECORDER.lock().unwrap().as_mut() {
+        let _ = recorder.record(&Token::Camera(camera));
+    }
+}
+
+/// Send a token to the display, through a remote socket if connected.
+pub fn send(py: Python<'_>, token: Token) -> PyResult<()> {
+    if let Some(recorder) = RECORDER.lock().unwrap().as_mut() {
+        recorder.record(&token)
+            .map_err(|err| PyRuntimeError::new_err(format!(
+                "could not record display session ({})",
+                err,
+            )))?;
+    }
+
+    if crate::socket::is_connected() {
+        return crate::socket::send(token)
+    }
+
+    match get(py)? {
+        #[cfg(feature = "ipc")]
+        Backend::Process => crate::ipc::send(py, token),
+        #[cfg(feature = "thread")]
+        Backend::Thread => {
+            match token {
+                Token::Assembly(assembly) => display::geometry::set_assembly(assembly),
+                Token::Camera(camera) => display::app::set_camera(camera),
+                Token::Clipping(clipping) => display::geometry::set_clipping(clipping),
+                Token::Close => display::geometry::set_close(),
+                Token::Events(columns) => display::event::set_columns(columns),
+                Token::Export(path) => display::export::set_export(path),
+                Token::Filter(filter) => display::event::set_filter(filter),
+                Token::Geometry(data) => display::geometry::set_data(data),
+                Token::Mesh(mesh) => display::geometry::set_mesh(mesh),
+                Token::Overlay(overlay) => display::event::set_overlay(overlay),
+                Token::Stop => display::app::set_exit(),
+                Token::Voxels(voxels) => display::event::set_voxels(voxels),
+            }
+            Ok(())
+        },
+    }
 }
 
-#[cfg(feature = "thread")]
 #[pyfunction]
-fn stop(_py: Python<'_>) -> PyResult<()> {
-    display::app::set_exit();
-    let handle = HANDLE
-        .lock()
-        .unwrap()
-        .take();
-    if let Some(handle) = handle {
-        match handle.join() {
-            Ok(_) => Ok(()),
-            Err(err) => std::panic::resume_unwind(err),
-        }
-    } else {
-        Ok(())
+fn stop(py: Python<'_>) -> PyResult<()> {
+    match BACKEND.get(py) {
+        None => Ok(()),
+        #[cfg(feature = "ipc")]
+        Some(Backend::Process) => crate::ipc::send_stop(py),
+        #[cfg(feature = "thread")]
+        Some(Backend::Thread) => {
+            display::app::set_exit();
+            let handle = HANDLE
+                .lock()
+                .unwrap()
+                .take();
+            if let Some(handle) = handle {
+                match handle.join() {
+                    Ok(_) => Ok(()),
+                    Err(err) => std::panic::resume_unwind(err),
+                }
+            } else {
+                Ok(())
+            }
+        },
     }
 }
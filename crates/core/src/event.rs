@@ -1,6 +1,12 @@
-use data::event::{CTrack, CVertex, Events};
+use data::event::{CTrack, CVertex, Columns};
+use data::ipc::Token;
 use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
 use super::numpy::{Dtype, PyArray};
+use super::path::PathString;
+use std::convert::Infallible;
+use std::ffi::OsStr;
+use std::path::Path;
 
 struct Iter<'a, T>
 where
@@ -39,21 +45,61 @@ where
     }
 }
 
-pub fn parse(data: &Bound<PyAny>) -> PyResult<()> {
-    let tracks = data.getattr("tracks")?;
-    let tracks: &PyArray<CTrack> = tracks.extract()?;
-    let vertices = data.getattr("vertices")?;
-    let vertices: &PyArray<CVertex> = vertices.extract()?;
+/// Iterate over the records of a C-contiguous array, which does not require the GIL.
+fn records<T: Copy>(slice: &[T]) -> impl Iterator<Item=Result<T, Infallible>> + '_ {
+    slice
+        .iter()
+        .map(|record| Ok(*record))
+}
 
-    let tracks = Iter::new(tracks);
-    let vertices = Iter::new(vertices);
-    let events = Events::new(tracks, vertices)?;
+/// Get a field of tracking data, either as an attribute or as an item.
+fn get_field<'py>(data: &Bound<'py, PyAny>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+    if data.hasattr(name)? {
+        data.getattr(name)
+    } else {
+        data.get_item(name)
+    }
+}
 
-    #[cfg(feature = "ipc")]
-    crate::ipc::send_events(data.py(), events)?;
+pub fn parse(data: &Bound<PyAny>) -> PyResult<()> {
+    let columns = extract(data)?;
+    crate::app::send(data.py(), Token::Events(columns))
+}
+
+/// Extract events from tracking data, or from a HepMC3 (ASCII) file.
+pub fn extract(data: &Bound<PyAny>) -> PyResult<Columns> {
+    if let Ok(path) = data.extract::<PathString>() {
+        let path = path.to_string();
+        let extension = Path::new(path.as_str())
+            .extension()
+            .and_then(OsStr::to_str);
+        return match extension {
+            Some("hepmc") | Some("hepmc3") => {
+                let events = data::hepmc::load(path.as_str())?;
+                Ok(Columns::from(&events))
+            },
+            _ => Err(PyValueError::new_err(format!(
+                "bad data (expected a '.hepmc3' file, found '{}')",
+                path,
+            ))),
+        }
+    }
 
-    #[cfg(feature = "thread")]
-    display::event::set(events);
+    let py = data.py();
+    let tracks = get_field(data, "tracks")?;
+    let tracks: &PyArray<CTrack> = tracks.extract()?;
+    let vertices = get_field(data, "vertices")?;
+    let vertices: &PyArray<CVertex> = vertices.extract()?;
 
-    Ok(())
+    let columns = match (tracks.as_slice(), vertices.as_slice()) {
+        (Some(tracks), Some(vertices)) => py.allow_threads(|| {
+            Columns::new(records(tracks), records(vertices))
+        })?,
+        _ => {
+            let tracks = Iter::new(tracks);
+            let vertices = Iter::new(vertices);
+            Columns::new(tracks, vertices)?
+        },
+    };
+    Ok(columns)
 }
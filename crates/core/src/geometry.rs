@@ -1,53 +1,138 @@
-use data::geometry::GeometryInfo;
+use data::geometry::{AssemblyInfo, GeometryInfo, MeshFileInfo, PartInfo, TransformInfo};
+use data::ipc::Token;
 use rmp_serde::Deserializer;
 use serde::Deserialize;
 use pyo3::prelude::*;
-use pyo3::exceptions::{PyNotImplementedError, PyTypeError};
-use pyo3::types::PyBytes;
+use pyo3::exceptions::{PyNotImplementedError, PyTypeError, PyValueError};
+use pyo3::types::{PyBytes, PyDict};
 use std::ffi::OsStr;
 use std::path::Path;
+use crate::path::PathString;
 
 
-pub fn load(py: Python, file: &str) -> PyResult<()> {
+pub fn load(
+    py: Python,
+    file: &str,
+    compute_normal: bool,
+    interpolate_normal: bool,
+    unit: f64,
+) -> PyResult<()> {
     let path = Path::new(file);
     match path.extension().and_then(OsStr::to_str) {
         Some("json") | Some("toml") | Some("yml") | Some("yaml") => {
             let data = load_data(py, file)?;
-
-            #[cfg(feature = "ipc")]
-            crate::ipc::send_data(py, data)?;
-
-            #[cfg(feature = "thread")]
-            display::geometry::set_data(data);
+            crate::app::send(py, Token::Geometry(data))?;
         },
-        Some("stl") => {
+        Some("gdml") => {
+            let data = data::gdml::load(path)?;
+            crate::app::send(py, Token::Geometry(data))?;
+        },
+        Some("glb") | Some("gltf") | Some("obj") | Some("ply") | Some("stl") => {
             let path = path
                 .canonicalize()?
                 .to_str()
                 .unwrap()
                 .to_string();
-
-            #[cfg(feature = "ipc")]
-            crate::ipc::send_stl(py, path)?;
-
-            #[cfg(feature = "thread")]
-            display::geometry::set_stl(path);
+            let mesh = MeshFileInfo { path, unit, compute_normal, interpolate_normal };
+            crate::app::send(py, Token::Mesh(mesh))?;
         }
         _ => return Err(PyNotImplementedError::new_err("")),
     }
     Ok(())
 }
 
-pub fn from_volume(volume: &Bound<PyAny>) -> PyResult<()> {
-    let data = extract_data(volume)?;
+/// Load a list of STL parts, each one given as a path or as a dict with placement options.
+pub fn from_parts(
+    py: Python,
+    parts: Vec<Bound<PyAny>>,
+    compute_normal: bool,
+    interpolate_normal: bool,
+) -> PyResult<()> {
+    let parts = parts
+        .iter()
+        .map(extract_part)
+        .collect::<PyResult<Vec<_>>>()?;
+    let assembly = AssemblyInfo {
+        name: "Assembly".to_string(),
+        parts,
+        compute_normal,
+        interpolate_normal,
+    };
+    crate::app::send(py, Token::Assembly(assembly))
+}
 
-    #[cfg(feature = "ipc")]
-    crate::ipc::send_data(volume.py(), data)?;
+fn extract_part(part: &Bound<PyAny>) -> PyResult<PartInfo> {
+    let mut unit = 1.0;
+    let mut name = None;
+    let mut position = [0.0; 3];
+    let mut rotation = None;
+    let mut color = None;
+    let path: PathString = match part.downcast::<PyDict>() {
+        Ok(dict) => {
+            let mut path = None;
+            for (key, value) in dict.iter() {
+                let key: String = key.extract()?;
+                match key.as_str() {
+                    "color" => color = Some(value.extract()?),
+                    "name" => name = Some(value.extract()?),
+                    "path" => path = Some(value.extract()?),
+                    "position" => position = value.extract()?,
+                    "rotation" => rotation = Some(value.extract::<[[f64; 3]; 3]>()?),
+                    "unit" => unit = extract_unit(&value)?,
+                    _ => return Err(PyValueError::new_err(format!(
+                        "bad part (unexpected '{}' key)",
+                        key,
+                    ))),
+                }
+            }
+            path.ok_or_else(|| PyValueError::new_err("bad part (missing 'path' key)"))?
+        },
+        Err(_) => part.extract()?,
+    };
 
-    #[cfg(feature = "thread")]
-    display::geometry::set_data(data);
+    let path = Path::new(path.to_string().as_str()).canonicalize()?;
+    let name = name.unwrap_or_else(|| path
+        .file_stem()
+        .and_then(OsStr::to_str)
+        .unwrap_or("Part")
+        .to_string()
+    );
+    let translation = std::array::from_fn(|i| position[i] * unit);
+    let rotation = match rotation {
+        Some(rotation) => std::array::from_fn(|i| std::array::from_fn(|j| rotation[j][i])),
+        None => [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    };
+    let path = path
+        .to_str()
+        .unwrap()
+        .to_string();
+    let transform = TransformInfo { translation, rotation };
+    Ok(PartInfo { name, path, unit, transform, color })
+}
 
-    Ok(())
+/// Return the length unit, in mm.
+pub fn extract_unit(unit: &Bound<PyAny>) -> PyResult<f64> {
+    if let Ok(unit) = unit.extract::<f64>() {
+        return Ok(unit)
+    }
+    let unit: String = unit.extract()?;
+    let value = match unit.as_str() {
+        "um" => 1E-03,
+        "mm" => 1.0,
+        "cm" => 1E+01,
+        "in" => 25.4,
+        "m" => 1E+03,
+        _ => return Err(PyValueError::new_err(format!(
+            "bad unit (expected 'um', 'mm', 'cm', 'in' or 'm', found '{}')",
+            unit,
+        ))),
+    };
+    Ok(value)
+}
+
+pub fn from_volume(volume: &Bound<PyAny>) -> PyResult<()> {
+    let data = extract_data(volume)?;
+    crate::app::send(volume.py(), Token::Geometry(data))
 }
 
 fn load_data(py: Python, path: &str) -> PyResult<GeometryInfo> {
@@ -1,17 +1,17 @@
-use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
+use ipc_channel::ipc::{IpcOneShotServer, IpcReceiver, IpcSender};
 use pyo3::prelude::*;
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::sync::GILOnceCell;
+use std::env::consts::EXE_SUFFIX;
 use std::process::{Child, Command};
 use std::sync::Mutex;
 
-use data::ipc::Token;
-use data::event::Events;
-use data::geometry::GeometryInfo;
+use data::geometry::Camera;
+use data::ipc::{Packet, Token};
 
 struct Pipe {
     process: Child,
-    tx: IpcSender<Token>,
+    tx: IpcSender<Packet>,
 }
 
 static PIPE: GILOnceCell<Mutex<Pipe>> = GILOnceCell::new();
@@ -24,13 +24,19 @@ pub(crate) fn spawn_agent(py: Python<'_>) -> PyResult<()> {
         .unwrap()
         .clone();
     path
-        .extend([".bins", "calzone-display-agent"]);
+        .extend([".bins", &format!("calzone-display-agent{}", EXE_SUFFIX)]);
     let process = Command::new(path)
         .arg(oss_name)
         .spawn()
         .map_err(|_| PyRuntimeError::new_err("could not spawn calzone-display-agent"))?;
-    let (_, tx): (_, IpcSender<Token>) = oss.accept()
+    let (_, (tx, camera)): (_, (IpcSender<Packet>, IpcReceiver<Camera>)) = oss.accept()
         .map_err(|_| PyRuntimeError::new_err("could not connect to display-oss"))?;
+    std::thread::spawn(move || {
+        // Forward camera changes reported by the agent, until it exits.
+        while let Ok(camera) = camera.recv() {
+            crate::app::report_camera(camera);
+        }
+    });
     let pipe = Pipe { process, tx };
     PIPE.set(py, Mutex::new(pipe))
         .map_err(|_| PyRuntimeError::new_err("could not set display-pipe"))?;
@@ -50,33 +56,15 @@ macro_rules! get_pipe {
     }
 }
 
-pub(crate) fn send_close(py: Python<'_>) -> PyResult<()> {
+pub(crate) fn send(py: Python<'_>, token: Token) -> PyResult<()> {
     let pipe = get_pipe!(py);
-    pipe.tx.send(Token::Close).unwrap();
-    Ok(())
-}
-
-pub(crate) fn send_data(py: Python<'_>, data: GeometryInfo) -> PyResult<()> {
-    let pipe = get_pipe!(py);
-    pipe.tx.send(Token::Geometry(data)).unwrap();
-    Ok(())
-}
-
-pub(crate) fn send_events(py: Python<'_>, events: Events) -> PyResult<()> {
-    let pipe = get_pipe!(py);
-    pipe.tx.send(Token::Events(events)).unwrap();
-    Ok(())
-}
-
-pub(crate) fn send_stl(py: Python<'_>, path: String) -> PyResult<()> {
-    let pipe = get_pipe!(py);
-    pipe.tx.send(Token::Stl(path)).unwrap();
+    pipe.tx.send(token.into()).unwrap();
     Ok(())
 }
 
 pub(crate) fn send_stop(py: Python<'_>) -> PyResult<()> {
     let mut pipe = get_pipe!(py);
-    pipe.tx.send(Token::Stop).unwrap();
+    pipe.tx.send(Token::Stop.into()).unwrap();
     let _ = pipe.process.wait();
     Ok(())
 }
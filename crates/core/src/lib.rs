@@ -1,6 +1,7 @@
+use data::ipc::Token;
 use process_path::get_dylib_path;
 use pyo3::prelude::*;
-use pyo3::exceptions::PySystemError;
+use pyo3::exceptions::{PyRuntimeError, PySystemError, PyValueError};
 use pyo3::sync::GILOnceCell;
 use std::path::{Path, PathBuf};
 
@@ -9,6 +10,7 @@ mod event;
 mod geometry;
 mod numpy;
 mod path;
+mod socket;
 
 #[cfg(feature = "ipc")]
 pub mod ipc;
@@ -32,35 +34,329 @@ fn set_prefix(py: Python) -> PyResult<()> {
     Ok(())
 }
 
+/// Clip the displayed geometry by planes, removing the half-spaces in front of them.
+///
+/// Each plane is an axis (e.g. 'x' or '-z'), a normal vector, or 'camera' for a plane facing the
+/// current view, optionally paired with an offset in mm, e.g. `('z', 100.0)`. Axis and vector
+/// planes are offset from the origin, along their normal, while camera planes are offset from the
+/// camera. Cut faces are capped with the volumes colour, and cut volumes are shaded with flat
+/// normals. If `tracks` is true, tracks are clipped as well. Calling `clip()` removes all planes.
+#[pyfunction]
+#[pyo3(name="clip", signature=(planes=None,/, *, tracks=false))]
+fn clip_display(
+    py: Python<'_>,
+    planes: Option<OneOrMany<PlaneArg>>,
+    tracks: bool,
+) -> PyResult<()> {
+    let planes = OneOrMany::into_vec(planes)
+        .into_iter()
+        .map(PlaneArg::into_plane)
+        .collect::<PyResult<Vec<_>>>()?;
+    let clipping = data::geometry::Clipping { planes, tracks };
+    app::send(py, Token::Clipping(clipping))
+}
+
+#[derive(FromPyObject)]
+enum PlaneArg {
+    Normal(NormalArg),
+    Offset((NormalArg, f64)),
+}
+
+#[derive(FromPyObject)]
+enum NormalArg {
+    Axis(String),
+    Vector([f64; 3]),
+}
+
+impl PlaneArg {
+    fn into_plane(self) -> PyResult<data::geometry::Plane> {
+        let (normal, offset) = match self {
+            Self::Normal(normal) => (normal, 0.0),
+            Self::Offset((normal, offset)) => (normal, offset),
+        };
+        let normal = match normal {
+            NormalArg::Axis(axis) => {
+                let vector = match axis.as_str() {
+                    "x" => [1.0, 0.0, 0.0],
+                    "y" => [0.0, 1.0, 0.0],
+                    "z" => [0.0, 0.0, 1.0],
+                    "-x" => [-1.0, 0.0, 0.0],
+                    "-y" => [0.0, -1.0, 0.0],
+                    "-z" => [0.0, 0.0, -1.0],
+                    "camera" => return Ok(data::geometry::Plane {
+                        normal: data::geometry::Normal::Camera,
+                        offset,
+                    }),
+                    _ => return Err(PyValueError::new_err(format!(
+                        "bad axis (expected 'x', 'y', 'z', '-x', '-y', '-z' or 'camera', \
+                            found '{}')",
+                        axis,
+                    ))),
+                };
+                data::geometry::Normal::Vector(vector)
+            },
+            NormalArg::Vector(vector) => {
+                let norm = vector.iter().map(|x| x * x).sum::<f64>().sqrt();
+                if (norm == 0.0) || !norm.is_finite() {
+                    return Err(PyValueError::new_err(format!(
+                        "bad normal (expected a finite non-null vector, found [{}, {}, {}])",
+                        vector[0],
+                        vector[1],
+                        vector[2],
+                    )))
+                }
+                data::geometry::Normal::Vector(vector.map(|x| x / norm))
+            },
+        };
+        Ok(data::geometry::Plane { normal, offset })
+    }
+}
+
 /// Close the current display.
 #[pyfunction]
 #[pyo3(name="close")]
-fn close_display(_py: Python<'_>) -> PyResult<()> {
-    #[cfg(feature = "ipc")]
-    {
-        crate::ipc::send_close(_py)
+fn close_display(py: Python<'_>) -> PyResult<()> {
+    app::send(py, Token::Close)
+}
+
+/// Configure the display, e.g. selecting the 'process' or 'thread' backend.
+///
+/// The backend must be configured before the first display, which otherwise starts the default
+/// one ('process' on MacOS, 'thread' elsewhere). If `record` is a path, subsequent displays are
+/// recorded to this session file, together with the camera viewpoint, which can be replayed with
+/// `calzone-display-agent --replay`. Recording is stopped with `record=False`.
+#[pyfunction]
+#[pyo3(name="configure", signature=(*, backend=None, record=None))]
+fn configure_display(
+    py: Python<'_>,
+    backend: Option<app::Backend>,
+    record: Option<RecordArg>,
+) -> PyResult<()> {
+    if let Some(backend) = backend {
+        app::configure(py, backend)?;
     }
+    match record {
+        Some(RecordArg::Path(path)) => app::record(Some(path.to_string().as_str()))?,
+        Some(RecordArg::Flag(false)) => app::record(None)?,
+        Some(RecordArg::Flag(true)) => return Err(PyValueError::new_err(
+            "bad record (expected a path or False, found True)"
+        )),
+        None => (),
+    }
+    Ok(())
+}
+
+#[derive(FromPyObject)]
+enum RecordArg<'py> {
+    Flag(bool),
+    Path(path::PathString<'py>),
+}
 
-    #[cfg(feature = "thread")]
-    {
-        display::geometry::set_close();
-        Ok(())
+/// Connect to a remote display agent, e.g. at 'tcp:localhost:7777' or 'unix:/tmp/display'.
+///
+/// Subsequent displays are sent to this agent, which must have been started separately with
+/// `calzone-display-agent --listen ADDRESS`. Note that STL paths are resolved agent-side.
+#[pyfunction]
+#[pyo3(name="connect", signature=(address,/))]
+fn connect_display(address: String) -> PyResult<()> {
+    socket::connect(address.as_str())
+}
+
+/// Export the displayed scene to a file, e.g. 'scene.glb' or 'event.json'.
+///
+/// The volumes hierarchy and the current event tracks are exported to glTF (as line strips).
+/// Events are exported to the Phoenix JSON format, either the displayed one or, if `data` is
+/// provided, all tracking `data` events (without any display).
+#[pyfunction]
+#[pyo3(name="export", signature=(path,/, *, data=None))]
+fn export_display(
+    py: Python<'_>,
+    path: path::PathString,
+    data: Option<&Bound<PyAny>>,
+) -> PyResult<()> {
+    let path = path.to_string();
+    let extension = Path::new(path.as_str())
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+    match (extension.as_deref(), data) {
+        (Some("json"), Some(data)) => {
+            let columns = event::extract(data)?;
+            let events: Vec<_> = columns.keys()
+                .filter_map(|index| columns.get(index).map(|event| (index, event)))
+                .collect();
+            let events = events.iter().map(|(index, event)| (*index, event));
+            return data::phoenix::write(path.as_str(), events)
+                .map_err(|err| PyRuntimeError::new_err(format!(
+                    "could not write '{}' ({})",
+                    path,
+                    err,
+                )))
+        },
+        (Some("glb"), Some(_)) => return Err(PyValueError::new_err(
+            "bad data (expected None for a '.glb' file, found tracking data)"
+        )),
+        (Some("glb"), None) | (Some("json"), None) => (),
+        _ => return Err(PyValueError::new_err(format!(
+            "bad path (expected a '.glb' or '.json' file, found '{}')",
+            path,
+        ))),
+    }
+    let path = std::path::absolute(path.as_str())?
+        .to_string_lossy()
+        .to_string();
+    app::send(py, Token::Export(path))
+}
+
+/// Filter the displayed events.
+///
+/// Tracks are hidden by PDG id (`pid`), by `creator` process, or if their initial kinetic energy
+/// (in MeV) is outside of the `energy` range, e.g. `(1.0, None)`. Vertices are hidden by `process`
+/// or by `volume` name. Selections are single values or lists of values. Calling `filter()`
+/// without arguments shows all tracks and vertices.
+#[pyfunction]
+#[pyo3(
+    name="filter",
+    signature=(*, pid=None, energy=None, creator=None, process=None, volume=None),
+)]
+fn filter_display(
+    py: Python<'_>,
+    pid: Option<OneOrMany<i32>>,
+    energy: Option<(Option<f32>, Option<f32>)>,
+    creator: Option<OneOrMany<String>>,
+    process: Option<OneOrMany<String>>,
+    volume: Option<OneOrMany<String>>,
+) -> PyResult<()> {
+    let (energy_min, energy_max) = energy.unwrap_or((None, None));
+    if let (Some(min), Some(max)) = (energy_min, energy_max) {
+        if min > max {
+            return Err(PyValueError::new_err(format!(
+                "bad energy (expected min <= max, found ({}, {}))",
+                min,
+                max,
+            )))
+        }
+    }
+    let filter = data::event::Filter {
+        pids: OneOrMany::into_vec(pid),
+        energy_min,
+        energy_max,
+        creators: OneOrMany::into_vec(creator),
+        processes: OneOrMany::into_vec(process),
+        volumes: OneOrMany::into_vec(volume),
+    };
+    app::send(py, Token::Filter(filter))
+}
+
+/// Overlay the displayed events.
+///
+/// `events` is either a boolean, enabling or disabling the overlay of all events, or a
+/// `(first, last)` range of event ids (inclusive), e.g. `(10, None)`. Tracks are coloured per
+/// event. If `density` is true, vertices are coloured by their spatial density. At most 100
+/// events are overlaid on the current one.
+#[pyfunction]
+#[pyo3(name="overlay", signature=(events=OverlayArg::Flag(true),/, *, density=false))]
+fn overlay_display(
+    py: Python<'_>,
+    events: OverlayArg,
+    density: bool,
+) -> PyResult<()> {
+    let overlay = match events {
+        OverlayArg::Flag(false) => None,
+        OverlayArg::Flag(true) => Some(data::event::Overlay { first: None, last: None, density }),
+        OverlayArg::Range((first, last)) => {
+            if let (Some(first), Some(last)) = (first, last) {
+                if first > last {
+                    return Err(PyValueError::new_err(format!(
+                        "bad events (expected first <= last, found ({}, {}))",
+                        first,
+                        last,
+                    )))
+                }
+            }
+            Some(data::event::Overlay { first, last, density })
+        },
+    };
+    app::send(py, Token::Overlay(overlay))
+}
+
+#[derive(FromPyObject)]
+enum OverlayArg {
+    Flag(bool),
+    Range((Option<usize>, Option<usize>)),
+}
+
+/// Show the vertices density of all events, as translucent voxels.
+///
+/// Vertices are binned over the geometry bounding box, with `bins` along its longest side. If
+/// `energy` is true, vertices are weighted by the kinetic energy lost by charged tracks instead
+/// of being counted. At most 256 bins are allowed. Calling `voxels(False)` hides the grid.
+#[pyfunction]
+#[pyo3(name="voxels", signature=(show=true,/, *, bins=64, energy=false))]
+fn voxels_display(
+    py: Python<'_>,
+    show: bool,
+    bins: usize,
+    energy: bool,
+) -> PyResult<()> {
+    if (bins == 0) || (bins > data::event::Voxels::MAX_BINS) {
+        return Err(PyValueError::new_err(format!(
+            "bad bins (expected a value in [1, {}], found {})",
+            data::event::Voxels::MAX_BINS,
+            bins,
+        )))
+    }
+    let voxels = show.then_some(data::event::Voxels { bins, energy });
+    app::send(py, Token::Voxels(voxels))
+}
+
+#[derive(FromPyObject)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T> OneOrMany<T> {
+    fn into_vec(values: Option<Self>) -> Vec<T> {
+        match values {
+            None => Vec::new(),
+            Some(Self::One(value)) => vec![value],
+            Some(Self::Many(values)) => values,
+        }
     }
 }
 
 /// Display a Calzone geometry.
+///
+/// A list of STL parts is displayed as a single assembly. Each part is either a path, or a dict
+/// with a 'path' and optional 'name', 'unit' (e.g. 'cm', or a length in mm), 'position' (in part
+/// units), 'rotation' (a 3x3 matrix) and 'color' (rgb) entries. The length `unit` of a single mesh
+/// file is given likewise, in mm by default.
+///
+/// Tracking `data` are either structured arrays of tracks and vertices, or the path to a HepMC3
+/// (ASCII) file, whose particles are displayed as straight tracks.
 #[pyfunction]
-#[pyo3(name="display", signature=(arg,/, *, data=None))]
+#[pyo3(
+    name="display",
+    signature=(arg,/, *, data=None, compute_normal=true, interpolate_normal=false, unit=None),
+)]
 fn update_display<'py>(
     py: Python<'py>,
     arg: DisplayArg<'py>,
     data: Option<&Bound<'py, PyAny>>,
+    compute_normal: bool,
+    interpolate_normal: bool,
+    unit: Option<&Bound<'py, PyAny>>,
 ) -> PyResult<()> {
     // Load the geometry.
     match arg {
         DisplayArg::Path(path) => {
             let path = path.to_string();
-            geometry::load(py, path.as_str())?;
+            let unit = unit.map(geometry::extract_unit).transpose()?.unwrap_or(1.0);
+            geometry::load(py, path.as_str(), compute_normal, interpolate_normal, unit)?;
+        },
+        DisplayArg::Parts(parts) => {
+            geometry::from_parts(py, parts, compute_normal, interpolate_normal)?
         },
         DisplayArg::Any(any) => geometry::from_volume(&any)?,
     }
@@ -76,6 +372,7 @@ fn update_display<'py>(
 #[derive(FromPyObject)]
 enum DisplayArg<'py> {
     Path(path::PathString<'py>),
+    Parts(Vec<Bound<'py, PyAny>>),
     Any(Bound<'py, PyAny>),
 }
 
@@ -90,12 +387,19 @@ fn init(module: &Bound<PyModule>) -> PyResult<()> {
     // Initialise the events interfaces.
     numpy::initialise(py)?;
 
-    // Spawn the display app.
-    app::spawn(module)?;
+    // Register the display app cleanup (the app itself is spawned lazily).
+    app::initialise(module)?;
 
     // Set the module's interface.
+    module.add_function(wrap_pyfunction!(clip_display, module)?)?;
     module.add_function(wrap_pyfunction!(close_display, module)?)?;
+    module.add_function(wrap_pyfunction!(configure_display, module)?)?;
+    module.add_function(wrap_pyfunction!(connect_display, module)?)?;
+    module.add_function(wrap_pyfunction!(export_display, module)?)?;
+    module.add_function(wrap_pyfunction!(filter_display, module)?)?;
+    module.add_function(wrap_pyfunction!(overlay_display, module)?)?;
     module.add_function(wrap_pyfunction!(update_display, module)?)?;
+    module.add_function(wrap_pyfunction!(voxels_display, module)?)?;
 
     Ok(())
 }
@@ -175,6 +175,14 @@ impl PyUntypedArray {
             .iter()
             .product::<npy_intp>() as usize
     }
+
+    /// Check if the array data are C-contiguous and aligned.
+    #[inline]
+    pub fn is_contiguous(&self) -> bool {
+        const FLAGS: c_int = PyArrayFlags::C_CONTIGUOUS | PyArrayFlags::ALIGNED;
+        let obj: &PyArrayObject = self.as_ref();
+        (obj.flags & FLAGS) == FLAGS
+    }
 }
 
 // Private interface.
@@ -276,6 +284,19 @@ where
         Ok(value)
     }
 
+    /// Get the array data as a slice, provided that they are C-contiguous.
+    pub fn as_slice(&self) -> Option<&[T]> {
+        if !self.is_contiguous() {
+            return None
+        }
+        let size = self.size();
+        if size == 0 {
+            return Some(&[])
+        }
+        let obj: &PyArrayObject = self.as_ref();
+        let slice = unsafe { std::slice::from_raw_parts(obj.data as *const T, size) };
+        Some(slice)
+    }
 }
 
 // Traits implementations.
@@ -334,12 +355,72 @@ where
     T: Dtype,
 {
     fn extract(obj: &'py PyAny) -> PyResult<Self> {
-        let untyped: &PyUntypedArray = FromPyObject::extract(obj)?;
+        if let Ok(untyped) = <&PyUntypedArray>::extract(obj) {
+            if let Ok(typed) = <&PyArray<T>>::try_from(untyped) {
+                return Ok(typed)
+            }
+        }
+        let converted = convert::<T>(&obj.as_borrowed())?.into_gil_ref();
+        let untyped: &PyUntypedArray = FromPyObject::extract(converted)?;
         let typed: &PyArray<T> = std::convert::TryFrom::try_from(untyped)?;
         Ok(typed)
     }
 }
 
+/// Convert a structured array, or a dict-like container of columns, to the layout of `T`.
+///
+/// Fields are matched by name and cast by numpy. Extra fields are ignored, while missing
+/// optional fields are zeroed.
+fn convert<'py, T>(obj: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>>
+where
+    T: Dtype,
+{
+    let py = obj.py();
+    let dtype = T::dtype(py)?.into_bound(py);
+    let names: Vec<String> = dtype.getattr("names")?.extract()?;
+
+    let columns: Vec<String> = if obj.hasattr("dtype")? {
+        let columns = obj.getattr("dtype")?.getattr("names")?;
+        if columns.is_none() {
+            return Err(PyTypeError::new_err(format!(
+                "bad dtype (expected a structured dtype, found '{}')",
+                obj.getattr("dtype")?,
+            )))
+        }
+        columns.extract()?
+    } else if obj.hasattr("keys")? {
+        obj.call_method0("keys")?
+            .iter()?
+            .map(|key| key?.extract())
+            .collect::<PyResult<_>>()?
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "bad array (expected a structured array or a mapping of columns, found '{}')",
+            obj.get_type().name()?,
+        )))
+    };
+
+    for name in names.iter() {
+        if !columns.contains(name) && !T::OPTIONAL.contains(&name.as_str()) {
+            return Err(PyTypeError::new_err(format!(
+                "bad dtype (missing '{}' field)",
+                name,
+            )))
+        }
+    }
+
+    let numpy = PyModule::import_bound(py, "numpy")?;
+    let column = |name: &str| -> PyResult<Bound<'py, PyAny>> {
+        numpy.call_method1("asarray", (obj.get_item(name)?,))
+    };
+    let shape = column(&names[0])?.getattr("shape")?;
+    let array = numpy.call_method1("zeros", (shape, &dtype))?;
+    for name in names.iter().filter(|name| columns.contains(name)) {
+        array.set_item(name, column(name)?)?;
+    }
+    Ok(array)
+}
+
 unsafe impl<T> PyNativeType for PyArray<T> {
     type AsRefSource = Self;
 }
@@ -352,6 +433,9 @@ unsafe impl<T> PyNativeType for PyArray<T> {
 // ===============================================================================================
 
 pub trait Dtype {
+    /// Fields which might be missing when converting from another layout.
+    const OPTIONAL: &'static [&'static str] = &[];
+
     fn dtype(py: Python) -> PyResult<PyObject>;
 }
 
@@ -363,6 +447,8 @@ impl Dtype for CTrack {
 }
 
 impl Dtype for CVertex {
+    const OPTIONAL: &'static [&'static str] = &["direction", "time"];
+
     #[inline]
     fn dtype(py: Python) -> PyResult<PyObject> {
         Ok(api(py).dtype_vertex.clone_ref(py))
@@ -380,6 +466,7 @@ pub enum PyArrayFlags {
 
 impl PyArrayFlags {
     pub const C_CONTIGUOUS: c_int = 0x0001;
+    pub const ALIGNED:      c_int = 0x0100;
     pub const WRITEABLE:    c_int = 0x0400;
 }
 
@@ -0,0 +1,47 @@
+use data::ipc::Token;
+use data::socket::{Address, Sender};
+use pyo3::prelude::*;
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use std::sync::Mutex;
+
+static SOCKET: Mutex<Option<Sender>> = Mutex::new(None);
+
+const LOCK_FAILED: &str = "could not lock display-socket";
+
+pub(crate) fn connect(address: &str) -> PyResult<()> {
+    let address: Address = address
+        .parse()
+        .map_err(|err: std::io::Error| PyValueError::new_err(err.to_string()))?;
+    let sender = Sender::connect(&address)
+        .map_err(|err| PyRuntimeError::new_err(format!(
+            "could not connect to display-agent ({})",
+            err,
+        )))?;
+    SOCKET
+        .lock()
+        .map_err(|_| PyRuntimeError::new_err(LOCK_FAILED))?
+        .replace(sender);
+    Ok(())
+}
+
+pub(crate) fn is_connected() -> bool {
+    SOCKET
+        .lock()
+        .map(|socket| socket.is_some())
+        .unwrap_or(false)
+}
+
+pub(crate) fn send(token: Token) -> PyResult<()> {
+    let mut socket = SOCKET
+        .lock()
+        .map_err(|_| PyRuntimeError::new_err(LOCK_FAILED))?;
+    let Some(sender) = socket.as_mut() else {
+        return Err(PyRuntimeError::new_err("display-socket is not connected"))
+    };
+    if let Err(err) = sender.send(&token) {
+        socket.take(); // The agent is gone, fall back to the local display.
+        let msg = format!("could not send to display-agent ({})", err);
+        return Err(PyRuntimeError::new_err(msg))
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// ===============================================================================================
+//
+// Chemical elements.
+//
+// ===============================================================================================
+
+/// Element symbols, by atomic number (starting from 1).
+pub const ELEMENTS: [&str; 118] = [
+    "H", "He", "Li", "Be", "B", "C", "N", "O", "F", "Ne", "Na", "Mg", "Al", "Si", "P", "S", "Cl",
+    "Ar", "K", "Ca", "Sc", "Ti", "V", "Cr", "Mn", "Fe", "Co", "Ni", "Cu", "Zn", "Ga", "Ge", "As",
+    "Se", "Br", "Kr", "Rb", "Sr", "Y", "Zr", "Nb", "Mo", "Tc", "Ru", "Rh", "Pd", "Ag", "Cd", "In",
+    "Sn", "Sb", "Te", "I", "Xe", "Cs", "Ba", "La", "Ce", "Pr", "Nd", "Pm", "Sm", "Eu", "Gd", "Tb",
+    "Dy", "Ho", "Er", "Tm", "Yb", "Lu", "Hf", "Ta", "W", "Re", "Os", "Ir", "Pt", "Au", "Hg", "Tl",
+    "Pb", "Bi", "Po", "At", "Rn", "Fr", "Ra", "Ac", "Th", "Pa", "U", "Np", "Pu", "Am", "Cm", "Bk",
+    "Cf", "Es", "Fm", "Md", "No", "Lr", "Rf", "Db", "Sg", "Bh", "Hs", "Mt", "Ds", "Rg", "Cn", "Nh",
+    "Fl", "Mc", "Lv", "Ts", "Og",
+];
+
+/// Atomic number of an element, from its symbol.
+pub fn atomic_number(symbol: &str) -> Option<usize> {
+    ELEMENTS
+        .iter()
+        .position(|s| *s == symbol)
+        .map(|i| i + 1)
+}
+
+/// Density (in g/cm3) and state of Geant4 NIST elements (`G4_H` to `G4_Cf`), by atomic number.
+pub const NIST_ELEMENTS: [(f64, &str); 98] = [
+    (8.37480E-05, "gas"), (1.66322E-04, "gas"), (0.534, "solid"), (1.848, "solid"),
+    (2.37, "solid"), (2.0, "solid"), (1.16520E-03, "gas"), (1.33151E-03, "gas"),
+    (1.58029E-03, "gas"), (8.38505E-04, "gas"), (0.971, "solid"), (1.74, "solid"),
+    (2.699, "solid"), (2.33, "solid"), (2.2, "solid"), (2.0, "solid"),
+    (2.99473E-03, "gas"), (1.66201E-03, "gas"), (0.862, "solid"), (1.55, "solid"),
+    (2.989, "solid"), (4.54, "solid"), (6.11, "solid"), (7.18, "solid"),
+    (7.44, "solid"), (7.874, "solid"), (8.9, "solid"), (8.902, "solid"),
+    (8.96, "solid"), (7.133, "solid"), (5.904, "solid"), (5.323, "solid"),
+    (5.73, "solid"), (4.5, "solid"), (7.07210E-03, "gas"), (3.47832E-03, "gas"),
+    (1.532, "solid"), (2.54, "solid"), (4.469, "solid"), (6.506, "solid"),
+    (8.57, "solid"), (10.22, "solid"), (11.5, "solid"), (12.41, "solid"),
+    (12.41, "solid"), (12.02, "solid"), (10.5, "solid"), (8.65, "solid"),
+    (7.31, "solid"), (7.31, "solid"), (6.691, "solid"), (6.24, "solid"),
+    (4.93, "solid"), (5.48536E-03, "gas"), (1.873, "solid"), (3.5, "solid"),
+    (6.154, "solid"), (6.657, "solid"), (6.71, "solid"), (6.9, "solid"),
+    (7.22, "solid"), (7.46, "solid"), (5.243, "solid"), (7.9004, "solid"),
+    (8.229, "solid"), (8.55, "solid"), (8.795, "solid"), (9.066, "solid"),
+    (9.321, "solid"), (6.73, "solid"), (9.84, "solid"), (13.31, "solid"),
+    (16.654, "solid"), (19.3, "solid"), (21.02, "solid"), (22.57, "solid"),
+    (22.42, "solid"), (21.45, "solid"), (19.32, "solid"), (13.546, "liquid"),
+    (11.72, "solid"), (11.35, "solid"), (9.747, "solid"), (9.32, "solid"),
+    (9.32, "solid"), (9.00662E-03, "gas"), (1.0, "solid"), (5.0, "solid"),
+    (10.07, "solid"), (11.72, "solid"), (15.37, "solid"), (18.95, "solid"),
+    (20.25, "solid"), (19.84, "solid"), (13.67, "solid"), (13.51, "solid"),
+    (14.0, "solid"), (10.0, "solid"),
+];
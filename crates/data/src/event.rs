@@ -1,6 +1,12 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
-use std::ffi::CStr;
+use std::collections::hash_map::Entry;
+use std::ops::Deref;
+
+#[cfg(feature = "ipc")]
+use ipc_channel::ipc::IpcSharedMemory;
+#[cfg(feature = "ipc")]
+use std::marker::PhantomData;
 
 
 // ===============================================================================================
@@ -41,75 +47,90 @@ pub struct Vertex {
     pub position: Vec3,
     pub process: String,
     pub volume: String,
+    /// Global time, in ns. Last, such that it defaults in sessions recorded without it.
+    #[serde(default)]
+    pub time: f32,
 }
 
-impl Events {
-    pub fn new<E, T, V>(
-        tracks: T,
-        vertices: V,
-    ) -> Result<Self, E>
-    where
-        E: std::error::Error,
-        T: IntoIterator<Item=Result<CTrack, E>>,
-        V: IntoIterator<Item=Result<CVertex, E>>,
-    {
-        let mut events: HashMap<usize, Event> = HashMap::new();
-        for track in tracks {
-            let track = track?;
-            events
-                .entry(track.event)
-                .and_modify(|event| {
-                    event.tracks.insert(track.tid, track.into());
-                })
-                .or_insert_with(|| {
-                    let mut event = Event::default();
-                    event.tracks.insert(track.tid, track.into());
-                    event
-                });
-        }
 
-        for vertex in vertices {
-            let vertex = vertex?;
-            events
-                .entry(vertex.event)
-                .and_modify(|event| {
-                    event.tracks
-                        .entry(vertex.tid)
-                        .and_modify(|track| {
-                            track.vertices.push(vertex.into());
-                        });
-                });
-        }
+// ===============================================================================================
+//
+// Events filter.
+//
+// ===============================================================================================
 
-        for event in events.values_mut() {
-            let mut daughters = HashMap::<i32, Vec<i32>>::new();
-            for track in event.tracks.values() {
-                if track.parent <= 0 {
-                    continue
-                }
-                daughters
-                    .entry(track.parent)
-                    .and_modify(|daughters| {
-                        daughters.push(track.tid);
-                    })
-                    .or_insert_with(|| vec![track.tid]);
-            }
-            for (tid, mut daughters) in daughters.drain() {
-                event.tracks
-                    .entry(tid)
-                    .and_modify(|track| {
-                        daughters.sort();
-                        track.daughters = daughters
-                    });
-            }
+/// Selection of displayed tracks and vertices. Tracks are hidden by PDG id, by creator process, or
+/// if their initial kinetic energy (in MeV) is out of range. Vertices are hidden by process or by
+/// volume name.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Filter {
+    pub pids: Vec<i32>,
+    pub energy_min: Option<f32>,
+    pub energy_max: Option<f32>,
+    pub creators: Vec<String>,
+    pub processes: Vec<String>,
+    pub volumes: Vec<String>,
+}
+
+impl Filter {
+    pub fn accepts_track(&self, track: &Track) -> bool {
+        if self.pids.contains(&track.pid) || self.creators.contains(&track.creator) {
+            return false
+        }
+        let energy = track.vertices.first().map(|vertex| vertex.energy);
+        match energy {
+            Some(energy) => {
+                self.energy_min.is_none_or(|min| energy >= min) &&
+                self.energy_max.is_none_or(|max| energy <= max)
+            },
+            None => true,
         }
+    }
+
+    pub fn accepts_vertex(&self, vertex: &Vertex) -> bool {
+        !self.processes.contains(&vertex.process) && !self.volumes.contains(&vertex.volume)
+    }
+}
+
+/// Selection of overlaid events, by id range (inclusive). Vertices are optionally coloured by
+/// their density.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Overlay {
+    pub first: Option<usize>,
+    pub last: Option<usize>,
+    pub density: bool,
+}
 
-        let events = Self(events);
-        Ok(events)
+impl Overlay {
+    pub fn contains(&self, event: usize) -> bool {
+        self.first.is_none_or(|first| event >= first) &&
+        self.last.is_none_or(|last| event <= last)
     }
 }
 
 
+/// Settings of the vertices density grid, with `bins` along the longest side of the geometry.
+/// Vertices are weighted by the energy lost by charged tracks if `energy` is set, or counted
+/// otherwise.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Voxels {
+    pub bins: usize,
+    pub energy: bool,
+}
+
+impl Voxels {
+    /// Maximum number of bins along the longest side.
+    pub const MAX_BINS: usize = 256;
+}
+
+impl Default for Voxels {
+    fn default() -> Self {
+        Self { bins: 64, energy: false }
+    }
+}
+
+
+
 // ===============================================================================================
 //
 // Input format (From NumPy arrays).
@@ -139,36 +160,377 @@ pub struct CVertex {
     pub process: [u8; 16],
 }
 
-impl From<CTrack> for Track {
-    fn from(track: CTrack) -> Self {
-        let daughters = Vec::new();
-        let creator = CStr::from_bytes_until_nul(&track.creator).unwrap();
-        let creator = creator.to_str().unwrap().to_string();
-        let vertices = Vec::new();
-        Self {
-            tid: track.tid,
-            parent: track.parent,
-            daughters,
-            pid: track.pid,
-            creator,
-            vertices,
+/// Decode a fixed width string, which is NUL terminated only if shorter than its field (numpy
+/// truncates longer strings).
+fn decode(bytes: &[u8]) -> String {
+    let bytes = bytes.split(|b| *b == 0).next().unwrap_or(bytes);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+
+// ===============================================================================================
+//
+// Columnar format (bulk transfer), with interned strings.
+//
+// ===============================================================================================
+
+/// Events in a columnar layout. Rows are grouped by event (sorted by id) and by track.
+#[derive(Default, Deserialize, Serialize)]
+pub struct Columns {
+    pub events: Column<EventRow>,
+    pub tracks: Column<TrackRow>,
+    pub vertices: Column<VertexRow>,
+    pub strings: Vec<String>,
+}
+
+/// An event, spanning `size` tracks from `start`.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[repr(C)]
+pub struct EventRow {
+    pub event: u64,
+    pub start: u64,
+    pub size: u64,
+}
+
+/// A track, spanning `size` vertices from `start`.
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[repr(C)]
+pub struct TrackRow {
+    pub tid: i32,
+    pub parent: i32,
+    pub pid: i32,
+    pub creator: u32,
+    pub start: u64,
+    pub size: u64,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+#[repr(C)]
+pub struct VertexRow {
+    pub energy: f32,
+    pub position: [f32; 3],
+    pub process: u32,
+    pub volume: u32,
+    /// Global time, in ns. Last, such that it defaults in sessions recorded without it.
+    #[serde(default)]
+    pub time: f32,
+}
+
+/// Plain data rows, which can be cast from / to bytes.
+///
+/// # Safety
+///
+/// Implementors must be `repr(C)`, without padding, and valid for any bit pattern.
+pub unsafe trait Row: Copy {}
+
+unsafe impl Row for EventRow {}
+unsafe impl Row for TrackRow {}
+unsafe impl Row for VertexRow {}
+
+/// A column of rows, either owned or mapped from shared memory (without copy).
+pub enum Column<T> {
+    Owned(Vec<T>),
+    #[cfg(feature = "ipc")]
+    Shared(IpcSharedMemory, PhantomData<T>),
+}
+
+impl<T> Default for Column<T> {
+    fn default() -> Self {
+        Self::Owned(Vec::new())
+    }
+}
+
+impl<T: Row> Deref for Column<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            Self::Owned(rows) => rows,
+            #[cfg(feature = "ipc")]
+            Self::Shared(bytes, _) => {
+                let size = bytes.len() / std::mem::size_of::<T>();
+                // Safety: alignment and size were checked when mapping the memory.
+                unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const T, size) }
+            },
+        }
+    }
+}
+
+impl<T: Row + Serialize> Serialize for Column<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Column<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::Owned)
+    }
+}
+
+#[cfg(feature = "ipc")]
+impl<T: Row> Column<T> {
+    /// Map rows from shared memory, or copy them if the memory is not suitably aligned.
+    pub(crate) fn from_shared(bytes: IpcSharedMemory) -> Self {
+        let size = std::mem::size_of::<T>();
+        if bytes.is_empty() {
+            Self::default()
+        } else if bytes.as_ptr().cast::<T>().is_aligned() && bytes.len().is_multiple_of(size) {
+            Self::Shared(bytes, PhantomData)
+        } else {
+            let rows = bytes
+                .chunks_exact(size)
+                .map(|chunk| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const T) })
+                .collect();
+            Self::Owned(rows)
         }
     }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        let rows: &[T] = self;
+        let size = std::mem::size_of_val(rows);
+        unsafe { std::slice::from_raw_parts(rows.as_ptr() as *const u8, size) }
+    }
 }
 
-impl From<CVertex> for Vertex {
-    fn from(vertex: CVertex) -> Self {
+impl Columns {
+    /// Build columns from tracks and vertices records. Vertices of unknown tracks are discarded,
+    /// and duplicated tracks replace former ones.
+    pub fn new<E, T, V>(
+        tracks: T,
+        vertices: V,
+    ) -> Result<Self, E>
+    where
+        E: std::error::Error,
+        T: IntoIterator<Item=Result<CTrack, E>>,
+        V: IntoIterator<Item=Result<CVertex, E>>,
+    {
+        let mut strings = Strings::default();
+
+        let mut rows: Vec<(usize, TrackRow)> = Vec::new();
+        let mut indices: HashMap<(usize, i32), usize> = HashMap::new();
+        for track in tracks {
+            let track = track?;
+            let row = TrackRow {
+                tid: track.tid,
+                parent: track.parent,
+                pid: track.pid,
+                creator: strings.intern_field(&track.creator),
+                start: 0,
+                size: 0,
+            };
+            match indices.entry((track.event, track.tid)) {
+                Entry::Occupied(entry) => rows[*entry.get()] = (track.event, row),
+                Entry::Vacant(entry) => {
+                    entry.insert(rows.len());
+                    rows.push((track.event, row));
+                },
+            }
+        }
+
         const CM: f32 = 1E-02;
-        let energy = vertex.energy as f32;
-        let position = Vec3 {
-            x: (vertex.position[0] as f32) * CM,
-            y: (vertex.position[1] as f32) * CM,
-            z: (vertex.position[2] as f32) * CM,
-        };
-        let process = CStr::from_bytes_until_nul(&vertex.process).unwrap();
-        let process = process.to_str().unwrap().to_string();
-        let volume = CStr::from_bytes_until_nul(&vertex.volume).unwrap();
-        let volume = volume.to_str().unwrap().to_string();
-        Self { energy, position, process, volume }
+        let mut vertices_rows: Vec<(usize, VertexRow)> = Vec::new();
+        for vertex in vertices {
+            let vertex = vertex?;
+            let Some(index) = indices.get(&(vertex.event, vertex.tid)) else { continue };
+            let row = VertexRow {
+                energy: vertex.energy as f32,
+                position: vertex.position.map(|x| (x as f32) * CM),
+                process: strings.intern_field(&vertex.process),
+                volume: strings.intern_field(&vertex.volume),
+                time: vertex.time as f32,
+            };
+            vertices_rows.push((*index, row));
+        }
+
+        // Order tracks by event, and vertices by track, preserving their relative order.
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        order.sort_by_key(|index| rows[*index].0);
+        let mut positions = vec![0; rows.len()];
+        for (position, index) in order.iter().enumerate() {
+            positions[*index] = position;
+        }
+        let mut sizes = vec![0_u64; rows.len()];
+        for (index, _) in vertices_rows.iter() {
+            sizes[*index] += 1;
+        }
+        vertices_rows.sort_by_key(|(index, _)| positions[*index]);
+
+        let mut events: Vec<EventRow> = Vec::new();
+        let mut tracks: Vec<TrackRow> = Vec::with_capacity(rows.len());
+        let mut start = 0;
+        for index in order {
+            let (event, mut row) = rows[index];
+            match events.last_mut() {
+                Some(last) if last.event == event as u64 => last.size += 1,
+                _ => events.push(EventRow {
+                    event: event as u64,
+                    start: tracks.len() as u64,
+                    size: 1,
+                }),
+            }
+            row.start = start;
+            row.size = sizes[index];
+            start += row.size;
+            tracks.push(row);
+        }
+        let vertices = vertices_rows
+            .into_iter()
+            .map(|(_, row)| row)
+            .collect();
+
+        Ok(Self {
+            events: Column::Owned(events),
+            tracks: Column::Owned(tracks),
+            vertices: Column::Owned(vertices),
+            strings: strings.values,
+        })
+    }
+
+    /// Number of events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Sorted event ids.
+    pub fn keys(&self) -> impl Iterator<Item=usize> + '_ {
+        self.events
+            .iter()
+            .map(|row| row.event as usize)
+    }
+
+    /// Reconstruct an event, given its index.
+    pub fn get(&self, event: usize) -> Option<Event> {
+        let index = self.events
+            .binary_search_by_key(&(event as u64), |row| row.event)
+            .ok()?;
+        let row = &self.events[index];
+        let tracks = &self.tracks[range(row.start, row.size)];
+
+        let mut daughters = HashMap::<i32, Vec<i32>>::new();
+        for track in tracks.iter() {
+            if track.parent > 0 {
+                daughters
+                    .entry(track.parent)
+                    .or_default()
+                    .push(track.tid);
+            }
+        }
+
+        let tracks = tracks
+            .iter()
+            .map(|track| {
+                let mut daughters = daughters.remove(&track.tid).unwrap_or_default();
+                daughters.sort();
+                let vertices = self.vertices[range(track.start, track.size)]
+                    .iter()
+                    .map(|vertex| Vertex {
+                        energy: vertex.energy,
+                        position: Vec3 {
+                            x: vertex.position[0],
+                            y: vertex.position[1],
+                            z: vertex.position[2],
+                        },
+                        process: self.strings[vertex.process as usize].clone(),
+                        volume: self.strings[vertex.volume as usize].clone(),
+                        time: vertex.time,
+                    })
+                    .collect();
+                let track = Track {
+                    tid: track.tid,
+                    parent: track.parent,
+                    daughters,
+                    pid: track.pid,
+                    creator: self.strings[track.creator as usize].clone(),
+                    vertices,
+                };
+                (track.tid, track)
+            })
+            .collect();
+        Some(Event { tracks })
     }
 }
+
+impl From<&Events> for Columns {
+    fn from(events: &Events) -> Self {
+        let mut strings = Strings::default();
+        let mut event_rows = Vec::new();
+        let mut track_rows = Vec::new();
+        let mut vertex_rows = Vec::new();
+
+        let mut keys: Vec<_> = events.0.keys().collect();
+        keys.sort();
+        for key in keys {
+            let event = &events.0[key];
+            event_rows.push(EventRow {
+                event: *key as u64,
+                start: track_rows.len() as u64,
+                size: event.tracks.len() as u64,
+            });
+            for track in event.tracks.values() {
+                track_rows.push(TrackRow {
+                    tid: track.tid,
+                    parent: track.parent,
+                    pid: track.pid,
+                    creator: strings.intern(&track.creator),
+                    start: vertex_rows.len() as u64,
+                    size: track.vertices.len() as u64,
+                });
+                for vertex in track.vertices.iter() {
+                    vertex_rows.push(VertexRow {
+                        energy: vertex.energy,
+                        position: [vertex.position.x, vertex.position.y, vertex.position.z],
+                        process: strings.intern(&vertex.process),
+                        volume: strings.intern(&vertex.volume),
+                        time: vertex.time,
+                    });
+                }
+            }
+        }
+        Self {
+            events: Column::Owned(event_rows),
+            tracks: Column::Owned(track_rows),
+            vertices: Column::Owned(vertex_rows),
+            strings: strings.values,
+        }
+    }
+}
+
+/// Interned strings, given as text or as fixed width fields.
+#[derive(Default)]
+struct Strings {
+    indices: HashMap<String, u32>,
+    fields: HashMap<[u8; 16], u32>,
+    values: Vec<String>,
+}
+
+impl Strings {
+    fn intern(&mut self, s: &str) -> u32 {
+        if let Some(index) = self.indices.get(s) {
+            return *index
+        }
+        let index = self.values.len() as u32;
+        self.indices.insert(s.to_string(), index);
+        self.values.push(s.to_string());
+        index
+    }
+
+    /// Intern a fixed width field, which is decoded only once.
+    fn intern_field(&mut self, field: &[u8; 16]) -> u32 {
+        if let Some(index) = self.fields.get(field) {
+            return *index
+        }
+        let index = self.intern(&decode(field));
+        self.fields.insert(*field, index);
+        index
+    }
+}
+
+fn range(start: u64, size: u64) -> std::ops::Range<usize> {
+    let start = start as usize;
+    start..(start + size as usize)
+}
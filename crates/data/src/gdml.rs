@@ -0,0 +1,946 @@
+use roxmltree::{Document, Node, ParsingOptions};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use super::elements::{ELEMENTS, NIST_ELEMENTS, atomic_number};
+use super::geometry::{
+    BoxInfo, GeometryInfo, MaterialInfo, MeshInfo, OrbInfo, SolidInfo, SphereInfo, TransformInfo,
+    TubsInfo, VolumeInfo,
+};
+
+
+// ===============================================================================================
+//
+// GDML reader.
+//
+// ===============================================================================================
+
+/// Load a GDML file, with lengths in mm and densities in g/cm3.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<GeometryInfo> {
+    let text = std::fs::read_to_string(path)?;
+    parse(&text)
+}
+
+pub fn parse(text: &str) -> io::Result<GeometryInfo> {
+    let options = ParsingOptions { allow_dtd: true, ..ParsingOptions::default() };
+    let document = Document::parse_with_options(text, options)
+        .map_err(|err| bad_gdml(&err.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "gdml" {
+        let msg = format!("expected a 'gdml' element, found '{}'", root.tag_name().name());
+        return Err(bad_gdml(&msg))
+    }
+
+    let mut reader = Reader::default();
+    let mut world = None;
+    for section in elements(root) {
+        match section.tag_name().name() {
+            "define" => reader.read_define(section)?,
+            "materials" => reader.index(section, Kind::Material),
+            "solids" => reader.index(section, Kind::Solid),
+            "structure" => reader.index(section, Kind::Volume),
+            "setup" if world.is_none() => {
+                world = child(section, "world")
+                    .and_then(|world| world.attribute("ref"));
+            },
+            _ => (),
+        }
+    }
+    let world = world.ok_or_else(|| bad_gdml("missing world volume"))?;
+
+    let volumes = reader.build_volume(world, world, Placement::default())?
+        .ok_or_else(|| bad_gdml(&format!("unsupported solid for world volume '{}'", world)))?;
+    let materials = reader.materials;
+    Ok(GeometryInfo { volumes, materials })
+}
+
+fn bad_gdml(reason: &str) -> io::Error {
+    let msg = format!("bad gdml ({})", reason);
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+#[derive(Default)]
+struct Reader<'a, 'input> {
+    constants: HashMap<String, f64>,
+    positions: HashMap<&'a str, [f64; 3]>,
+    rotations: HashMap<&'a str, [f64; 3]>,
+    nodes: HashMap<(Kind, &'a str), Node<'a, 'input>>,
+    materials: HashMap<String, MaterialInfo>,
+    /// Volumes and assemblies being built, in order to detect recursive definitions.
+    ancestors: Vec<&'a str>,
+}
+
+#[derive(Clone, Copy, Eq, Hash, PartialEq)]
+enum Kind {
+    Assembly,
+    Element,
+    Isotope,
+    Material,
+    Solid,
+    Volume,
+}
+
+impl<'a, 'input> Reader<'a, 'input> {
+    fn index(&mut self, section: Node<'a, 'input>, kind: Kind) {
+        for node in elements(section) {
+            let Some(name) = node.attribute("name") else { continue };
+            let kind = match (kind, node.tag_name().name()) {
+                (Kind::Material, "element") => Kind::Element,
+                (Kind::Material, "isotope") => Kind::Isotope,
+                (Kind::Material, "material") => Kind::Material,
+                (Kind::Material, _) => continue,
+                (Kind::Volume, "assembly") => Kind::Assembly,
+                (Kind::Volume, "volume") => Kind::Volume,
+                (Kind::Volume, _) => continue,
+                (kind, _) => kind,
+            };
+            self.nodes.insert((kind, name), node);
+        }
+    }
+
+    fn get(&self, kind: Kind, name: &str) -> Option<Node<'a, 'input>> {
+        self.nodes.get(&(kind, name)).copied()
+    }
+
+    fn read_define(&mut self, section: Node<'a, 'input>) -> io::Result<()> {
+        for node in elements(section) {
+            let Some(name) = node.attribute("name") else { continue };
+            match node.tag_name().name() {
+                "constant" | "variable" => {
+                    let value = self.value(node, "value", None)?;
+                    self.constants.insert(name.to_string(), value);
+                },
+                "expression" => {
+                    let value = self.eval(node.text().unwrap_or(""))?;
+                    self.constants.insert(name.to_string(), value);
+                },
+                "quantity" => {
+                    let value = self.value(node, "value", None)?;
+                    let unit = self.unit(node, "unit", "1")?;
+                    self.constants.insert(name.to_string(), value * unit);
+                },
+                "position" => {
+                    let position = self.vector(node, "unit", "mm")?;
+                    self.positions.insert(name, position);
+                },
+                "rotation" => {
+                    let rotation = self.vector(node, "unit", "rad")?;
+                    self.rotations.insert(name, rotation);
+                },
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+
+// ===============================================================================================
+//
+// Structure (volumes and placements).
+//
+// ===============================================================================================
+
+/// An active transform, in the mother frame.
+#[derive(Clone, Copy)]
+struct Placement {
+    translation: [f64; 3],
+    rotation: [[f64; 3]; 3], // Row major.
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        let rotation = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+        Self { translation: [0.0; 3], rotation }
+    }
+}
+
+impl Placement {
+    /// Compose with a local transform, expressed in this frame.
+    fn then(&self, local: &Self) -> Self {
+        let r = &self.rotation;
+        let rotation = std::array::from_fn(|i| std::array::from_fn(|j| {
+            (0..3).map(|k| r[i][k] * local.rotation[k][j]).sum()
+        }));
+        let translation = std::array::from_fn(|i| {
+            let rt: f64 = (0..3).map(|k| r[i][k] * local.translation[k]).sum();
+            rt + self.translation[i]
+        });
+        Self { translation, rotation }
+    }
+}
+
+impl From<Placement> for TransformInfo {
+    fn from(placement: Placement) -> Self {
+        // Rotations are stored by columns.
+        let r = &placement.rotation;
+        let rotation = std::array::from_fn(|i| std::array::from_fn(|j| r[j][i]));
+        Self { translation: placement.translation, rotation }
+    }
+}
+
+/// Maximum nesting of volumes and assemblies.
+const MAX_DEPTH: usize = 64;
+
+impl<'a, 'input> Reader<'a, 'input> {
+    /// Build a volume and its daughters. Volumes with an unsupported solid are skipped (`None`),
+    /// with a warning.
+    fn build_volume(
+        &mut self,
+        name: &'a str,
+        label: &str,
+        placement: Placement,
+    ) -> io::Result<Option<VolumeInfo>> {
+        let node = self.get(Kind::Volume, name)
+            .ok_or_else(|| bad_gdml(&format!("undefined volume '{}'", name)))?;
+        let solid = reference(node, "solidref")?;
+        let Some(solid) = self.build_solid(solid)? else {
+            let tag = self.get(Kind::Solid, solid)
+                .unwrap() // The solid exists, since it was read.
+                .tag_name()
+                .name();
+            eprintln!(
+                "warning: skipping volume '{}' (unsupported '{}' solid '{}')",
+                strip_pointer(label),
+                tag,
+                solid,
+            );
+            return Ok(None)
+        };
+        let material = reference(node, "materialref")?;
+        self.load_material(material)?;
+
+        self.enter(name)?;
+        let mut daughters = Vec::new();
+        for child in elements(node) {
+            match child.tag_name().name() {
+                "physvol" => self.place(child, &Placement::default(), &mut daughters)?,
+                tag @ ("divisionvol" | "paramvol" | "replicavol") => {
+                    let msg = format!("unsupported '{}' in volume '{}'", tag, name);
+                    return Err(bad_gdml(&msg))
+                },
+                _ => (),
+            }
+        }
+        self.ancestors.pop();
+
+        let volume = VolumeInfo {
+            name: strip_pointer(label).to_string(),
+            solid,
+            material: material.to_string(),
+            transform: placement.into(),
+            daughters,
+        };
+        Ok(Some(volume))
+    }
+
+    /// Enter a volume or an assembly, checking for recursive or too deep definitions.
+    fn enter(&mut self, name: &'a str) -> io::Result<()> {
+        if self.ancestors.contains(&name) {
+            return Err(bad_gdml(&format!("recursive definition of '{}'", name)))
+        }
+        if self.ancestors.len() >= MAX_DEPTH {
+            let msg = format!("too deep hierarchy (expected at most {} levels)", MAX_DEPTH);
+            return Err(bad_gdml(&msg))
+        }
+        self.ancestors.push(name);
+        Ok(())
+    }
+
+    fn place(
+        &mut self,
+        physvol: Node<'a, 'input>,
+        mother: &Placement,
+        daughters: &mut Vec<VolumeInfo>,
+    ) -> io::Result<()> {
+        let volume = reference(physvol, "volumeref")?;
+
+        let mut local = Placement::default();
+        for child in elements(physvol) {
+            match child.tag_name().name() {
+                "position" => local.translation = self.vector(child, "unit", "mm")?,
+                "positionref" => local.translation = lookup(&self.positions, child, "position")?,
+                "rotation" => local.rotation = rotation(self.vector(child, "unit", "rad")?),
+                "rotationref" => {
+                    local.rotation = rotation(lookup(&self.rotations, child, "rotation")?)
+                },
+                _ => (),
+            }
+        }
+        let placement = mother.then(&local);
+
+        if let Some(assembly) = self.get(Kind::Assembly, volume) {
+            // Assemblies are flattened into the mother volume.
+            self.enter(volume)?;
+            for child in elements(assembly).filter(|child| child.tag_name().name() == "physvol") {
+                self.place(child, &placement, daughters)?;
+            }
+            self.ancestors.pop();
+        } else {
+            let label = physvol.attribute("name").unwrap_or(volume);
+            if let Some(volume) = self.build_volume(volume, label, placement)? {
+                daughters.push(volume);
+            }
+        }
+        Ok(())
+    }
+
+}
+
+/// Get a referenced position or rotation.
+fn lookup(defines: &HashMap<&str, [f64; 3]>, node: Node, what: &str) -> io::Result<[f64; 3]> {
+    let name = node.attribute("ref")
+        .ok_or_else(|| bad_gdml(&format!("missing '{}' reference", what)))?;
+    defines.get(name)
+        .copied()
+        .ok_or_else(|| bad_gdml(&format!("undefined {} '{}'", what, name)))
+}
+
+/// Active rotation matrix corresponding to GDML angles (which define the frame rotation).
+fn rotation(angles: [f64; 3]) -> [[f64; 3]; 3] {
+    let (sx, cx) = (-angles[0]).sin_cos();
+    let (sy, cy) = (-angles[1]).sin_cos();
+    let (sz, cz) = (-angles[2]).sin_cos();
+    let rx = [[1.0, 0.0, 0.0], [0.0, cx, -sx], [0.0, sx, cx]];
+    let ry = [[cy, 0.0, sy], [0.0, 1.0, 0.0], [-sy, 0.0, cy]];
+    let rz = [[cz, -sz, 0.0], [sz, cz, 0.0], [0.0, 0.0, 1.0]];
+    let product = |a: [[f64; 3]; 3], b: [[f64; 3]; 3]| -> [[f64; 3]; 3] {
+        std::array::from_fn(|i| std::array::from_fn(|j| {
+            (0..3).map(|k| a[i][k] * b[k][j]).sum()
+        }))
+    };
+    product(product(rx, ry), rz)
+}
+
+/// Strip any pointer suffix from Geant4 exported names, e.g. `World0x5581c0a0`.
+fn strip_pointer(name: &str) -> &str {
+    match name.rfind("0x") {
+        Some(i) if i > 0 && name[(i + 2)..].chars().all(|c| c.is_ascii_hexdigit()) => &name[..i],
+        _ => name,
+    }
+}
+
+
+// ===============================================================================================
+//
+// Solids.
+//
+// ===============================================================================================
+
+impl<'a, 'input> Reader<'a, 'input> {
+    /// Build a solid, or `None` if its type is not supported.
+    fn build_solid(&self, name: &str) -> io::Result<Option<SolidInfo>> {
+        let node = self.get(Kind::Solid, name)
+            .ok_or_else(|| bad_gdml(&format!("undefined solid '{}'", name)))?;
+        let lunit = self.unit(node, "lunit", "mm")?;
+        let aunit = self.unit(node, "aunit", "rad")?;
+        let length = |attr: &str, default: Option<f64>| -> io::Result<f64> {
+            Ok(self.value(node, attr, default)? * lunit)
+        };
+        let angle = |attr: &str, default: Option<f64>| -> io::Result<f64> {
+            Ok(self.value(node, attr, default)? * aunit)
+        };
+
+        let solid = match node.tag_name().name() {
+            "box" => SolidInfo::Box(BoxInfo {
+                size: [length("x", None)?, length("y", None)?, length("z", None)?],
+                displacement: [0.0; 3],
+            }),
+            "orb" => SolidInfo::Orb(OrbInfo {
+                radius: length("r", None)?,
+                displacement: [0.0; 3],
+            }),
+            "sphere" => SolidInfo::Sphere(SphereInfo {
+                inner_radius: length("rmin", Some(0.0))?,
+                outer_radius: length("rmax", None)?,
+                start_phi: angle("startphi", Some(0.0))?,
+                delta_phi: angle("deltaphi", None)?,
+                start_theta: angle("starttheta", Some(0.0))?,
+                delta_theta: angle("deltatheta", None)?,
+            }),
+            "tessellated" => SolidInfo::Mesh(self.build_tessellated(node)?),
+            "tube" => SolidInfo::Tubs(TubsInfo {
+                inner_radius: length("rmin", Some(0.0))?,
+                outer_radius: length("rmax", None)?,
+                length: length("z", None)?,
+                start_phi: angle("startphi", Some(0.0))?,
+                delta_phi: angle("deltaphi", None)?,
+                displacement: [0.0; 3],
+            }),
+            _ => return Ok(None),
+        };
+        Ok(Some(solid))
+    }
+
+    fn build_tessellated(&self, node: Node) -> io::Result<MeshInfo> {
+        let mut facets = Vec::new();
+        for facet in elements(node) {
+            let n = match facet.tag_name().name() {
+                "triangular" => 3,
+                "quadrangular" => 4,
+                _ => continue,
+            };
+            let mut vertices = [[0.0; 3]; 4];
+            for (i, vertex) in vertices.iter_mut().take(n).enumerate() {
+                let attr = format!("vertex{}", i + 1);
+                let name = facet.attribute(attr.as_str())
+                    .ok_or_else(|| bad_gdml(&format!("missing '{}' attribute", attr)))?;
+                *vertex = self.positions.get(name)
+                    .copied()
+                    .ok_or_else(|| bad_gdml(&format!("undefined position '{}'", name)))?;
+            }
+            if facet.attribute("type") == Some("RELATIVE") {
+                let origin = vertices[0];
+                for vertex in vertices[1..n].iter_mut() {
+                    *vertex = std::array::from_fn(|j| vertex[j] + origin[j]);
+                }
+            }
+            let triangles: &[[usize; 3]] = if n == 3 {
+                &[[0, 1, 2]]
+            } else {
+                &[[0, 1, 2], [0, 2, 3]]
+            };
+            for triangle in triangles {
+                for i in triangle {
+                    facets.extend(vertices[*i].iter().map(|x| *x as f32));
+                }
+            }
+        }
+        Ok(MeshInfo(facets))
+    }
+}
+
+
+// ===============================================================================================
+//
+// Materials.
+//
+// ===============================================================================================
+
+impl<'a, 'input> Reader<'a, 'input> {
+    fn load_material(&mut self, name: &str) -> io::Result<()> {
+        if self.materials.contains_key(name) {
+            return Ok(())
+        }
+        let material = match self.get(Kind::Material, name) {
+            Some(node) => {
+                let density = match child(node, "D") {
+                    Some(density) => {
+                        let unit = match density.attribute("unit").unwrap_or("g/cm3") {
+                            "g/cm3" | "g/cm^3" => 1.0,
+                            "mg/cm3" | "mg/cm^3" | "kg/m3" | "kg/m^3" => 1E-03,
+                            unit => {
+                                let msg = format!("unsupported density unit '{}'", unit);
+                                return Err(bad_gdml(&msg))
+                            },
+                        };
+                        self.value(density, "value", None)? * unit
+                    },
+                    None => {
+                        let msg = format!("missing density for material '{}'", name);
+                        return Err(bad_gdml(&msg))
+                    },
+                };
+                let state = node.attribute("state").unwrap_or("solid").to_string();
+                let composition = self.composition(node)?;
+                MaterialInfo { density, state, composition }
+            },
+            None => nist_material(name)
+                .ok_or_else(|| bad_gdml(&format!("undefined material '{}'", name)))?,
+        };
+        self.materials.insert(name.to_string(), material);
+        Ok(())
+    }
+
+    /// Mass composition of a material, by element symbol.
+    fn composition(&self, material: Node) -> io::Result<Vec<(String, f64)>> {
+        if material.attribute("Z").is_some() {
+            let symbol = self.symbol_from_z(material)?;
+            return Ok(vec![(symbol, 1.0)])
+        }
+
+        let mut composition: Vec<(String, f64)> = Vec::new();
+        let mut add = |symbol: String, weight: f64| {
+            match composition.iter_mut().find(|(s, _)| *s == symbol) {
+                Some((_, w)) => *w += weight,
+                None => composition.push((symbol, weight)),
+            }
+        };
+        for component in elements(material) {
+            let tag = component.tag_name().name();
+            if (tag != "fraction") && (tag != "composite") {
+                continue
+            }
+            let name = component.attribute("ref")
+                .ok_or_else(|| bad_gdml(&format!("missing 'ref' attribute in '{}'", tag)))?;
+            let n = self.value(component, "n", None)?;
+            if let Some(element) = self.get(Kind::Element, name) {
+                let weight = if tag == "composite" { n * self.mass(element)? } else { n };
+                add(self.symbol(element)?, weight);
+            } else if let Some(other) = self.get(Kind::Material, name) {
+                for (symbol, weight) in self.composition(other)? {
+                    add(symbol, n * weight);
+                }
+            } else if let Some(other) = nist_material(name) {
+                for (symbol, weight) in other.composition {
+                    add(symbol, n * weight);
+                }
+            } else {
+                let msg = format!("undefined element or material '{}'", name);
+                return Err(bad_gdml(&msg))
+            }
+        }
+
+        let total: f64 = composition.iter().map(|(_, weight)| weight).sum();
+        if total > 0.0 {
+            for (_, weight) in composition.iter_mut() {
+                *weight /= total;
+            }
+        }
+        Ok(composition)
+    }
+
+    fn symbol(&self, element: Node) -> io::Result<String> {
+        match element.attribute("formula") {
+            Some(formula) => Ok(formula.to_string()),
+            None if element.attribute("Z").is_some() => self.symbol_from_z(element),
+            None => {
+                // Isotopic element, e.g. enriched Uranium.
+                let isotope = child(element, "fraction")
+                    .and_then(|fraction| fraction.attribute("ref"))
+                    .and_then(|name| self.get(Kind::Isotope, name))
+                    .ok_or_else(|| bad_gdml("missing element formula"))?;
+                self.symbol_from_z(isotope)
+            },
+        }
+    }
+
+    fn symbol_from_z(&self, node: Node) -> io::Result<String> {
+        let z = self.value(node, "Z", None)?.round() as usize;
+        ELEMENTS
+            .get(z.wrapping_sub(1))
+            .map(|symbol| symbol.to_string())
+            .ok_or_else(|| bad_gdml(&format!("bad atomic number ({})", z)))
+    }
+
+    /// Molar mass of an element, in g/mole.
+    fn mass(&self, element: Node) -> io::Result<f64> {
+        if let Some(atom) = child(element, "atom") {
+            return self.value(atom, "value", None)
+        }
+        let mut total = 0.0;
+        let mut mass = 0.0;
+        for fraction in elements(element).filter(|node| node.tag_name().name() == "fraction") {
+            let n = self.value(fraction, "n", None)?;
+            let isotope = fraction.attribute("ref")
+                .and_then(|name| self.get(Kind::Isotope, name))
+                .ok_or_else(|| bad_gdml("undefined isotope"))?;
+            let atom = child(isotope, "atom")
+                .ok_or_else(|| bad_gdml("missing isotope mass"))?;
+            mass += n * self.value(atom, "value", None)?;
+            total += n;
+        }
+        if total > 0.0 {
+            Ok(mass / total)
+        } else {
+            Err(bad_gdml("missing element mass"))
+        }
+    }
+}
+
+/// Fallback for common Geant4 (NIST) materials, which GDML exports only reference.
+fn nist_material(name: &str) -> Option<MaterialInfo> {
+    let material = |density: f64, state: &str, composition: &[(&str, f64)]| MaterialInfo {
+        density,
+        state: state.to_string(),
+        composition: composition
+            .iter()
+            .map(|(symbol, weight)| (symbol.to_string(), *weight))
+            .collect(),
+    };
+    let name = strip_pointer(name).strip_prefix("G4_")?;
+    let material = match name {
+        "AIR" => material(1.20479E-03, "gas", &[("N", 0.755), ("O", 0.232), ("Ar", 0.013)]),
+        "Galactic" => material(1E-25, "gas", &[("H", 1.0)]),
+        "WATER" => material(1.0, "liquid", &[("H", 0.112), ("O", 0.888)]),
+        symbol => {
+            let z = atomic_number(symbol)?;
+            let (density, state) = NIST_ELEMENTS.get(z - 1)?;
+            material(*density, state, &[(symbol, 1.0)])
+        },
+    };
+    Some(material)
+}
+
+
+
+// ===============================================================================================
+//
+// Attributes and expressions.
+//
+// ===============================================================================================
+
+impl<'a, 'input> Reader<'a, 'input> {
+    fn value(&self, node: Node, attr: &str, default: Option<f64>) -> io::Result<f64> {
+        match (node.attribute(attr), default) {
+            (Some(expr), _) => self.eval(expr),
+            (None, Some(default)) => Ok(default),
+            (None, None) => {
+                let msg = format!(
+                    "missing '{}' attribute in '{}'",
+                    attr,
+                    node.attribute("name").unwrap_or(node.tag_name().name()),
+                );
+                Err(bad_gdml(&msg))
+            },
+        }
+    }
+
+    fn unit(&self, node: Node, attr: &str, default: &str) -> io::Result<f64> {
+        self.eval(node.attribute(attr).unwrap_or(default))
+    }
+
+    fn vector(&self, node: Node, attr: &str, default: &str) -> io::Result<[f64; 3]> {
+        let unit = self.unit(node, attr, default)?;
+        Ok([
+            self.value(node, "x", Some(0.0))? * unit,
+            self.value(node, "y", Some(0.0))? * unit,
+            self.value(node, "z", Some(0.0))? * unit,
+        ])
+    }
+
+    fn eval(&self, expr: &str) -> io::Result<f64> {
+        let mut parser = Parser { constants: &self.constants, chars: expr.chars().peekable() };
+        parser
+            .parse()
+            .ok_or_else(|| bad_gdml(&format!("bad expression '{}'", expr)))
+    }
+}
+
+/// A recursive descent parser for arithmetic expressions.
+struct Parser<'a, I: Iterator<Item=char>> {
+    constants: &'a HashMap<String, f64>,
+    chars: std::iter::Peekable<I>,
+}
+
+impl<'a, I: Iterator<Item=char>> Parser<'a, I> {
+    fn parse(&mut self) -> Option<f64> {
+        let value = self.sum()?;
+        self.skip_whitespaces();
+        self.chars.peek().is_none().then_some(value)
+    }
+
+    fn sum(&mut self) -> Option<f64> {
+        let mut value = self.product()?;
+        loop {
+            match self.next_operator(&['+', '-']) {
+                Some('+') => value += self.product()?,
+                Some(_) => value -= self.product()?,
+                None => return Some(value),
+            }
+        }
+    }
+
+    fn product(&mut self) -> Option<f64> {
+        let mut value = self.unary()?;
+        loop {
+            match self.next_operator(&['*', '/']) {
+                Some('*') => value *= self.unary()?,
+                Some(_) => value /= self.unary()?,
+                None => return Some(value),
+            }
+        }
+    }
+
+    /// Unary signs bind looser than powers, e.g. `-2^2 = -4`.
+    fn unary(&mut self) -> Option<f64> {
+        match self.next_operator(&['+', '-']) {
+            Some('-') => Some(-self.unary()?),
+            Some(_) => self.unary(),
+            None => self.power(),
+        }
+    }
+
+    /// Right associative power, whose exponent might be signed, e.g. `2^-1`.
+    fn power(&mut self) -> Option<f64> {
+        let value = self.primary()?;
+        match self.next_operator(&['^']) {
+            Some(_) => Some(value.powf(self.unary()?)),
+            None => Some(value),
+        }
+    }
+
+    fn primary(&mut self) -> Option<f64> {
+        self.skip_whitespaces();
+        let c = *self.chars.peek()?;
+        if c == '(' {
+            self.chars.next();
+            let value = self.sum()?;
+            self.next_operator(&[')'])?;
+            Some(value)
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut number = String::new();
+            while let Some(&c) = self.chars.peek() {
+                let exponent = number.ends_with(['e', 'E']);
+                if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' ||
+                    (exponent && (c == '+' || c == '-')) {
+                    number.push(c);
+                    self.chars.next();
+                } else {
+                    break
+                }
+            }
+            number.parse().ok()
+        } else if c.is_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&c) = self.chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    name.push(c);
+                    self.chars.next();
+                } else {
+                    break
+                }
+            }
+            if self.next_operator(&['(']).is_some() {
+                let mut args = vec![self.sum()?];
+                while self.next_operator(&[',']).is_some() {
+                    args.push(self.sum()?);
+                }
+                self.next_operator(&[')'])?;
+                function(name.as_str(), &args)
+            } else {
+                self.constants
+                    .get(name.as_str())
+                    .copied()
+                    .or_else(|| builtin(name.as_str()))
+            }
+        } else {
+            None
+        }
+    }
+
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespaces();
+        let c = *self.chars.peek()?;
+        if operators.contains(&c) {
+            self.chars.next();
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+}
+
+/// Built-in constants and units (with lengths in mm and angles in rad, as CLHEP).
+fn builtin(name: &str) -> Option<f64> {
+    use std::f64::consts::PI;
+    let value = match name {
+        "pi" => PI,
+        "twopi" => 2.0 * PI,
+        "halfpi" => 0.5 * PI,
+        "nm" | "nanometer" => 1E-06,
+        "um" | "micrometer" => 1E-03,
+        "mm" | "millimeter" => 1.0,
+        "cm" | "centimeter" => 1E+01,
+        "m" | "meter" => 1E+03,
+        "km" | "kilometer" => 1E+06,
+        "rad" | "radian" => 1.0,
+        "mrad" | "milliradian" => 1E-03,
+        "deg" | "degree" => PI / 180.0,
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn function(name: &str, args: &[f64]) -> Option<f64> {
+    let value = match (name, args) {
+        ("abs", [x]) => x.abs(),
+        ("acos", [x]) => x.acos(),
+        ("asin", [x]) => x.asin(),
+        ("atan", [x]) => x.atan(),
+        ("atan2", [y, x]) => y.atan2(*x),
+        ("cos", [x]) => x.cos(),
+        ("exp", [x]) => x.exp(),
+        ("log", [x]) => x.ln(),
+        ("log10", [x]) => x.log10(),
+        ("max", [x, y]) => x.max(*y),
+        ("min", [x, y]) => x.min(*y),
+        ("pow", [x, y]) => x.powf(*y),
+        ("sin", [x]) => x.sin(),
+        ("sqrt", [x]) => x.sqrt(),
+        ("tan", [x]) => x.tan(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+
+// ===============================================================================================
+//
+// XML helpers.
+//
+// ===============================================================================================
+
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item=Node<'a, 'input>> {
+    node.children().filter(|child| child.is_element())
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    elements(node).find(|child| child.tag_name().name() == tag)
+}
+
+fn reference<'a>(node: Node<'a, '_>, tag: &str) -> io::Result<&'a str> {
+    child(node, tag)
+        .and_then(|child| child.attribute("ref"))
+        .ok_or_else(|| {
+            let msg = format!(
+                "missing '{}' in '{}'",
+                tag,
+                node.attribute("name").unwrap_or(node.tag_name().name()),
+            );
+            bad_gdml(&msg)
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GDML: &str = r#"<?xml version="1.0"?>
+<gdml>
+  <define>
+    <constant name="size" value="2 * m"/>
+    <position name="below" z="-1" unit="cm"/>
+    <rotation name="quarter" z="90" unit="deg"/>
+  </define>
+  <materials>
+    <element name="Hydrogen" formula="H" Z="1"><atom value="1.008"/></element>
+    <element name="Oxygen" formula="O" Z="8"><atom value="15.999"/></element>
+    <material name="Water" state="liquid">
+      <D value="1" unit="g/cm3"/>
+      <composite n="2" ref="Hydrogen"/>
+      <composite n="1" ref="Oxygen"/>
+    </material>
+  </materials>
+  <solids>
+    <box name="WorldBox" x="size" y="size" z="size"/>
+    <tube name="Tube" rmax="1" z="2" deltaphi="2 * pi" lunit="cm"/>
+    <cone name="Cone" rmax1="1" rmax2="2" z="3" deltaphi="360" aunit="deg"/>
+  </solids>
+  <structure>
+    <volume name="Target"><materialref ref="Water"/><solidref ref="Tube"/></volume>
+    <volume name="Funnel"><materialref ref="G4_Fe"/><solidref ref="Cone"/></volume>
+    <volume name="World0x5581c0a0">
+      <materialref ref="G4_AIR"/>
+      <solidref ref="WorldBox"/>
+      <physvol name="Target0x5581c0b0">
+        <volumeref ref="Target"/>
+        <position name="shift" x="1" unit="cm"/>
+        <rotationref ref="quarter"/>
+      </physvol>
+      <physvol><volumeref ref="Funnel"/><positionref ref="below"/></physvol>
+    </volume>
+  </structure>
+  <setup name="Default" version="1.0"><world ref="World0x5581c0a0"/></setup>
+</gdml>"#;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() <= 1E-09 * b.abs().max(1.0), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn expressions() {
+        let mut reader = Reader::default();
+        reader.constants.insert("size".to_string(), 3.0);
+        let eval = |expr: &str| reader.eval(expr).unwrap();
+        assert_close(eval("1 + 2 * 3"), 7.0);
+        assert_close(eval("(1 + 2) * 3"), 9.0);
+        assert_close(eval("-2^2"), -4.0);
+        assert_close(eval("2^-1"), 0.5);
+        assert_close(eval("2^3^2"), 512.0);
+        assert_close(eval("2 * -size"), -6.0);
+        assert_close(eval("1.5e+1 / 3"), 5.0);
+        assert_close(eval("max(size, 2) + sqrt(4)"), 5.0);
+        assert!(reader.eval("1 +").is_err());
+        assert!(reader.eval("undefined").is_err());
+    }
+
+    #[test]
+    fn units() {
+        let reader = Reader::default();
+        let eval = |expr: &str| reader.eval(expr).unwrap();
+        assert_close(eval("1 * cm"), 10.0);
+        assert_close(eval("2 * m"), 2000.0);
+        assert_close(eval("5 * um"), 5E-03);
+        assert_close(eval("180 * deg"), std::f64::consts::PI);
+        assert_close(eval("mrad"), 1E-03);
+    }
+
+    #[test]
+    fn placements() {
+        let geometry = parse(GDML).unwrap();
+        let world = &geometry.volumes;
+        assert_eq!(world.name, "World");
+        let SolidInfo::Box(ref solid) = world.solid else { panic!() };
+        assert_eq!(solid.size, [2000.0; 3]);
+
+        // The cone volume is skipped, as unsupported.
+        assert_eq!(world.daughters.len(), 1);
+        let target = &world.daughters[0];
+        assert_eq!(target.name, "Target");
+        let SolidInfo::Tubs(ref solid) = target.solid else { panic!() };
+        assert_close(solid.outer_radius, 10.0);
+        assert_close(solid.length, 20.0);
+        assert_close(solid.delta_phi, 2.0 * std::f64::consts::PI);
+
+        // GDML rotations are frame rotations, thus x is mapped to -y.
+        let transform = &target.transform;
+        let expected = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+        for (column, expected) in transform.rotation.iter().zip(expected.iter()) {
+            for (x, y) in column.iter().zip(expected.iter()) {
+                assert_close(*x, *y);
+            }
+        }
+        assert_eq!(transform.translation, [10.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn recursive() {
+        let gdml = GDML.replace("\"Funnel\"/>", "\"World0x5581c0a0\"/>");
+        let Err(err) = parse(&gdml) else { panic!() };
+        assert!(err.to_string().contains("recursive"), "{}", err);
+    }
+
+    #[test]
+    fn materials() {
+        let geometry = parse(GDML).unwrap();
+        let mut names: Vec<_> = geometry.materials.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, ["G4_AIR", "Water"]); // Skipped volumes materials are not loaded.
+
+        let water = &geometry.materials["Water"];
+        assert_eq!(water.state, "liquid");
+        assert_close(water.density, 1.0);
+        let [(ref h, wh), (ref o, wo)] = water.composition[..] else { panic!() };
+        assert_eq!((h.as_str(), o.as_str()), ("H", "O"));
+        assert_close(wh, 2.0 * 1.008 / (2.0 * 1.008 + 15.999));
+        assert_close(wh + wo, 1.0);
+    }
+
+    #[test]
+    fn nist() {
+        let lead = nist_material("G4_Pb0x1234").unwrap();
+        assert_close(lead.density, 11.35);
+        assert_eq!(lead.state, "solid");
+        assert_eq!(lead.composition, [("Pb".to_string(), 1.0)]);
+        assert_eq!(nist_material("G4_AIR").unwrap().state, "gas");
+        assert!(nist_material("G4_Xx").is_none());
+        assert!(nist_material("Pb").is_none());
+    }
+}
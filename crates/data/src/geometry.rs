@@ -74,3 +74,64 @@ pub struct MaterialInfo {
     pub state: String,
     pub composition: Vec<(String, f64)>,
 }
+
+/// A set of mesh files (STL) placed under a common root volume.
+#[derive(Deserialize, Serialize)]
+pub struct AssemblyInfo {
+    pub name: String,
+    pub parts: Vec<PartInfo>,
+    pub compute_normal: bool,
+    pub interpolate_normal: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PartInfo {
+    pub name: String,
+    pub path: String,
+    /// Length unit of the file, in mm.
+    pub unit: f64,
+    pub transform: TransformInfo,
+    pub color: Option<[f32; 3]>,
+}
+
+/// A mesh file (glTF, OBJ, PLY or STL). Normals settings only apply to STL files.
+#[derive(Deserialize, Serialize)]
+pub struct MeshFileInfo {
+    pub path: String,
+    /// Length unit of the file, in mm.
+    pub unit: f64,
+    pub compute_normal: bool,
+    pub interpolate_normal: bool,
+}
+
+/// Drone camera placement and field of view, in the display (view) frame, for replaying
+/// sessions. The translation is in m and the field of view in rad.
+#[derive(Clone, Copy, Deserialize, PartialEq, Serialize)]
+pub struct Camera {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub fov: f32,
+}
+
+/// Clipping planes, removing the half-spaces in front of them. Tracks are clipped as well if
+/// `tracks` is set.
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct Clipping {
+    pub planes: Vec<Plane>,
+    pub tracks: bool,
+}
+
+/// A clipping plane, at `offset` along its normal (in mm), from the origin or from the camera.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Plane {
+    pub normal: Normal,
+    pub offset: f64,
+}
+
+#[derive(Clone, Copy, Deserialize, Serialize)]
+pub enum Normal {
+    /// A fixed direction, in the world frame.
+    Vector([f64; 3]),
+    /// The camera view direction, when the plane is set.
+    Camera,
+}
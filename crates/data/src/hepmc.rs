@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+use super::event::{Event, Events, Track, Vec3, Vertex};
+
+
+// ===============================================================================================
+//
+// HepMC3 ASCII reader.
+//
+// ===============================================================================================
+
+/// Length of final state tracks (in m), which have no end vertex.
+pub const FINAL_LENGTH: f64 = 1.0;
+
+/// Speed of light, in m/ns.
+const C_LIGHT: f64 = 0.299792458;
+
+/// Load a HepMC3 ASCII file, with particles as straight tracks from their production vertex to
+/// their end vertex. Positions are in m, times in ns and kinetic energies in MeV.
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Events> {
+    let text = std::fs::read_to_string(path)?;
+    parse(&text)
+}
+
+pub fn parse(text: &str) -> io::Result<Events> {
+    let mut events = HashMap::new();
+    let mut current: Option<Record> = None;
+    let mut units = Units::default();
+    let mut started = false;
+    for (lineno, line) in text.lines().enumerate() {
+        let bad_line = |reason: &str| bad_hepmc(&format!("line {}: {}", lineno + 1, reason));
+        let line = line.trim();
+        if line.starts_with("HepMC::") {
+            match line {
+                "HepMC::Asciiv3-START_EVENT_LISTING" => started = true,
+                "HepMC::Asciiv3-END_EVENT_LISTING" => break,
+                _ => (),
+            }
+            continue
+        }
+        let mut words = line.split_ascii_whitespace();
+        let Some(tag) = words.next() else { continue };
+        if !started {
+            return Err(bad_line("missing event listing header"))
+        }
+        let words: Vec<&str> = words.collect();
+        match tag {
+            "E" => {
+                if let Some(record) = current.take() {
+                    events.insert(record.number, record.build(&units));
+                }
+                current = Some(Record::new(&words).ok_or_else(|| bad_line("bad event"))?);
+                units = Units::default();
+            },
+            "U" => units = Units::new(&words).ok_or_else(|| bad_line("bad units"))?,
+            "P" => {
+                let record = current.as_mut().ok_or_else(|| bad_line("particle outside event"))?;
+                let particle = Particle::new(&words).ok_or_else(|| bad_line("bad particle"))?;
+                record.particles.push(particle);
+            },
+            "V" => {
+                let record = current.as_mut().ok_or_else(|| bad_line("vertex outside event"))?;
+                let (id, vertex) = GenVertex::new(&words).ok_or_else(|| bad_line("bad vertex"))?;
+                record.vertices.insert(id, vertex);
+            },
+            _ => (), // e.g. attributes, weights or run info.
+        }
+    }
+    if let Some(record) = current.take() {
+        events.insert(record.number, record.build(&units));
+    }
+    Ok(Events(events))
+}
+
+fn bad_hepmc(reason: &str) -> io::Error {
+    let msg = format!("bad hepmc ({})", reason);
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
+
+
+// ===============================================================================================
+//
+// Event records.
+//
+// ===============================================================================================
+
+struct Units {
+    energy: f64,
+    length: f64,
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self { energy: 1E+03, length: 1E-03 } // GeV and mm.
+    }
+}
+
+impl Units {
+    fn new(words: &[&str]) -> Option<Self> {
+        let [energy, length] = words else { return None };
+        let energy = match *energy {
+            "GEV" => 1E+03,
+            "MEV" => 1.0,
+            _ => return None,
+        };
+        let length = match *length {
+            "MM" => 1E-03,
+            "CM" => 1E-02,
+            _ => return None,
+        };
+        Some(Self { energy, length })
+    }
+}
+
+struct Record {
+    number: usize,
+    position: [f64; 4],
+    particles: Vec<Particle>,
+    vertices: HashMap<i32, GenVertex>,
+}
+
+struct Particle {
+    id: i32,
+    parent: i32,
+    pid: i32,
+    momentum: [f64; 4],
+    mass: f64,
+    status: i32,
+}
+
+struct GenVertex {
+    status: i32,
+    incoming: Vec<i32>,
+    position: Option<[f64; 4]>,
+}
+
+impl Record {
+    fn new(words: &[&str]) -> Option<Self> {
+        let number = words.first()?.parse().ok()?;
+        let position = parse_position(words)?.unwrap_or([0.0; 4]);
+        let particles = Vec::new();
+        let vertices = HashMap::new();
+        Some(Self { number, position, particles, vertices })
+    }
+
+    fn build(self, units: &Units) -> Event {
+        // Map particles to their end vertices. Positive parents refer to implicit vertices,
+        // located at the event position.
+        let mut ends: HashMap<i32, Option<i32>> = HashMap::new();
+        for (id, vertex) in self.vertices.iter() {
+            for particle in vertex.incoming.iter() {
+                ends.insert(*particle, Some(*id));
+            }
+        }
+        for particle in self.particles.iter() {
+            if particle.parent > 0 {
+                ends.entry(particle.parent).or_insert(None);
+            }
+        }
+
+        let position = |vertex: Option<i32>| {
+            let position = vertex
+                .and_then(|vertex| self.vertices.get(&vertex))
+                .and_then(|vertex| vertex.position)
+                .unwrap_or(self.position);
+            position.map(|x| x * units.length)
+        };
+
+        let mut tracks = HashMap::new();
+        for particle in self.particles.iter() {
+            let energy = ((particle.momentum[3] - particle.mass) * units.energy) as f32;
+            let (start, parent, process) = match particle.parent {
+                0 => (None, 0, String::new()),
+                parent if parent > 0 => (Some(position(None)), parent, String::new()),
+                vertex => {
+                    let (parent, process) = match self.vertices.get(&vertex) {
+                        Some(v) => (v.incoming.first().copied().unwrap_or(0), v.process()),
+                        None => (0, String::new()),
+                    };
+                    (Some(position(Some(vertex))), parent, process)
+                },
+            };
+            let end = match ends.get(&particle.id) {
+                Some(vertex) => Some((position(*vertex), self.process(*vertex))),
+                None => start.and_then(|start| {
+                    let [px, py, pz, e] = particle.momentum;
+                    let norm = (px * px + py * py + pz * pz).sqrt();
+                    (norm > 0.0).then(|| {
+                        let scale = FINAL_LENGTH / norm;
+                        let end = [
+                            start[0] + px * scale,
+                            start[1] + py * scale,
+                            start[2] + pz * scale,
+                            start[3] + e * scale, // c t = L / beta.
+                        ];
+                        (end, String::new())
+                    })
+                }),
+            };
+
+            let mut vertices = Vec::with_capacity(2);
+            if let Some(start) = start {
+                vertices.push(make_vertex(start, energy, process));
+            }
+            if let Some((end, process)) = end {
+                vertices.push(make_vertex(end, energy, process));
+            }
+            let track = Track {
+                tid: particle.id,
+                parent,
+                daughters: Vec::new(),
+                pid: particle.pid,
+                creator: format!("status {}", particle.status),
+                vertices,
+            };
+            tracks.insert(particle.id, track);
+        }
+
+        let mut daughters = HashMap::<i32, Vec<i32>>::new();
+        for track in tracks.values() {
+            if track.parent > 0 {
+                daughters.entry(track.parent).or_default().push(track.tid);
+            }
+        }
+        for (tid, mut daughters) in daughters.drain() {
+            if let Some(track) = tracks.get_mut(&tid) {
+                daughters.sort();
+                track.daughters = daughters;
+            }
+        }
+        Event { tracks }
+    }
+
+    fn process(&self, vertex: Option<i32>) -> String {
+        vertex
+            .and_then(|vertex| self.vertices.get(&vertex))
+            .map(GenVertex::process)
+            .unwrap_or_default()
+    }
+}
+
+impl Particle {
+    fn new(words: &[&str]) -> Option<Self> {
+        let [id, parent, pid, px, py, pz, e, mass, status, ..] = words else { return None };
+        let id = id.parse().ok()?;
+        let parent = parent.parse().ok()?;
+        let pid = pid.parse().ok()?;
+        let momentum = [px.parse().ok()?, py.parse().ok()?, pz.parse().ok()?, e.parse().ok()?];
+        let mass = mass.parse().ok()?;
+        let status = status.parse().ok()?;
+        Some(Self { id, parent, pid, momentum, mass, status })
+    }
+}
+
+impl GenVertex {
+    fn new(words: &[&str]) -> Option<(i32, Self)> {
+        let [id, status, rest @ ..] = words else { return None };
+        let id = id.parse().ok()?;
+        let status = status.parse().ok()?;
+
+        // Incoming particles, e.g. '[1,2]', possibly with spaces.
+        let end = rest.iter().position(|word| *word == "@").unwrap_or(rest.len());
+        let incoming = rest[..end]
+            .join("")
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .filter(|id| !id.is_empty())
+            .map(|id| id.parse().ok())
+            .collect::<Option<Vec<i32>>>()?;
+        let position = parse_position(words)?;
+        Some((id, Self { status, incoming, position }))
+    }
+
+    fn process(&self) -> String {
+        format!("status {}", self.status)
+    }
+}
+
+/// Parse an optional '@ x y z t' position, with time as a length (c t).
+fn parse_position(words: &[&str]) -> Option<Option<[f64; 4]>> {
+    let Some(index) = words.iter().position(|word| *word == "@") else { return Some(None) };
+    let [x, y, z, t, ..] = &words[(index + 1)..] else { return None };
+    Some(Some([x.parse().ok()?, y.parse().ok()?, z.parse().ok()?, t.parse().ok()?]))
+}
+
+fn make_vertex(position: [f64; 4], energy: f32, process: String) -> Vertex {
+    let time = (position[3] / C_LIGHT) as f32;
+    let position = Vec3 {
+        x: position[0] as f32,
+        y: position[1] as f32,
+        z: position[2] as f32,
+    };
+    Vertex { energy, position, time, process, volume: String::new() }
+}
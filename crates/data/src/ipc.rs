@@ -1,14 +1,105 @@
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use std::io::{self, ErrorKind, Read, Write};
 
-use super::event::Events;
-use super::geometry::GeometryInfo;
+use super::event::{Columns, Filter, Overlay, Voxels};
+use super::geometry::{AssemblyInfo, Camera, Clipping, GeometryInfo, MeshFileInfo};
+
+#[cfg(feature = "ipc")]
+use ipc_channel::ipc::IpcSharedMemory;
+#[cfg(feature = "ipc")]
+use super::event::Column;
 
 
 #[derive(Serialize, Deserialize)]
 pub enum Token {
+    Assembly(AssemblyInfo),
+    Camera(Camera),
+    Clipping(Clipping),
     Close,
-    Events(Events),
+    Events(Columns),
+    Export(String),
+    Filter(Filter),
     Geometry(GeometryInfo),
+    Mesh(MeshFileInfo),
+    Overlay(Option<Overlay>),
     Stop,
-    Stl(String),
+    Voxels(Option<Voxels>),
+}
+
+/// Tokens sent through the agent pipe, with events moved in shared memory.
+#[cfg(feature = "ipc")]
+#[derive(Serialize, Deserialize)]
+pub enum Packet {
+    Token(Token),
+    Events(SharedEvents),
+}
+
+#[cfg(feature = "ipc")]
+impl From<Token> for Packet {
+    fn from(token: Token) -> Self {
+        match token {
+            Token::Events(columns) => Self::Events(columns.into()),
+            token => Self::Token(token),
+        }
+    }
+}
+
+/// Columnar events, with bulk rows in shared memory.
+#[cfg(feature = "ipc")]
+#[derive(Serialize, Deserialize)]
+pub struct SharedEvents {
+    events: IpcSharedMemory,
+    tracks: IpcSharedMemory,
+    vertices: IpcSharedMemory,
+    strings: Vec<String>,
+}
+
+#[cfg(feature = "ipc")]
+impl From<Columns> for SharedEvents {
+    fn from(columns: Columns) -> Self {
+        Self {
+            events: IpcSharedMemory::from_bytes(columns.events.as_bytes()),
+            tracks: IpcSharedMemory::from_bytes(columns.tracks.as_bytes()),
+            vertices: IpcSharedMemory::from_bytes(columns.vertices.as_bytes()),
+            strings: columns.strings,
+        }
+    }
+}
+
+#[cfg(feature = "ipc")]
+impl From<SharedEvents> for Columns {
+    fn from(shared: SharedEvents) -> Self {
+        Self {
+            events: Column::from_shared(shared.events),
+            tracks: Column::from_shared(shared.tracks),
+            vertices: Column::from_shared(shared.vertices),
+            strings: shared.strings,
+        }
+    }
+}
+
+/// Write a msgpack encoded value.
+pub(crate) fn encode<W, T>(writer: &mut W, value: &T) -> io::Result<()>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    rmp_serde::encode::write(writer, value)
+        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+    writer.flush()
+}
+
+/// Read a msgpack encoded value, or `None` at the end of the stream.
+pub(crate) fn decode<R, T>(reader: &mut R) -> io::Result<Option<T>>
+where
+    R: Read,
+    T: DeserializeOwned,
+{
+    match rmp_serde::decode::from_read(reader) {
+        Ok(value) => Ok(Some(value)),
+        Err(rmp_serde::decode::Error::InvalidMarkerRead(err))
+            if err.kind() == ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(io::Error::new(ErrorKind::InvalidData, err)),
+    }
 }
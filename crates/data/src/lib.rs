@@ -1,3 +1,9 @@
+pub mod elements;
 pub mod event;
+pub mod gdml;
 pub mod geometry;
+pub mod hepmc;
 pub mod ipc;
+pub mod phoenix;
+pub mod session;
+pub mod socket;
@@ -0,0 +1,83 @@
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::event::{Event, Track, Vec3};
+
+
+// ===============================================================================================
+//
+// Phoenix (web event display) JSON format.
+//
+// ===============================================================================================
+
+const MM: f32 = 1E+03;
+
+/// Convert events to the Phoenix JSON format. Tracks are exported as polylines, and their
+/// vertices as hits. Lengths are in mm.
+pub fn to_json<'a, I>(events: I) -> Value
+where
+    I: IntoIterator<Item=(usize, &'a Event)>,
+{
+    let mut root = Map::new();
+    for (index, event) in events {
+        root.insert(format!("Event {}", index), convert_event(index, event));
+    }
+    Value::Object(root)
+}
+
+/// Write events to a Phoenix JSON file.
+pub fn write<'a, P, I>(path: P, events: I) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    I: IntoIterator<Item=(usize, &'a Event)>,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, &to_json(events))?;
+    writer.flush()
+}
+
+fn convert_event(index: usize, event: &Event) -> Value {
+    let mut tracks: Vec<&Track> = event.tracks.values().collect();
+    tracks.sort_by_key(|track| track.tid);
+
+    let hits: Vec<Value> = tracks
+        .iter()
+        .flat_map(|track| track.vertices.iter().map(|vertex| json!({
+            "type": "Point",
+            "pos": to_mm(&vertex.position),
+            "tid": track.tid,
+            "energy": vertex.energy,
+            "time": vertex.time,
+            "process": vertex.process,
+            "volume": vertex.volume,
+        })))
+        .collect();
+
+    let tracks: Vec<Value> = tracks
+        .iter()
+        .map(|track| json!({
+            "pos": track.vertices
+                .iter()
+                .map(|vertex| to_mm(&vertex.position))
+                .collect::<Vec<_>>(),
+            "tid": track.tid,
+            "parent": track.parent,
+            "pid": track.pid,
+            "energy": track.vertices.first().map(|vertex| vertex.energy),
+            "creator": track.creator,
+        }))
+        .collect();
+
+    json!({
+        "event number": index,
+        "run number": 0,
+        "Tracks": { "Tracks": tracks },
+        "Hits": { "Vertices": hits },
+    })
+}
+
+fn to_mm(position: &Vec3) -> [f32; 3] {
+    [position.x * MM, position.y * MM, position.z * MM]
+}
@@ -0,0 +1,71 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind};
+use std::path::Path;
+use std::time::Instant;
+
+use super::ipc::{decode, encode, Token};
+
+
+// ===============================================================================================
+//
+// Display sessions, i.e. timestamped sequences of tokens.
+//
+// ===============================================================================================
+
+const MAGIC: &str = "calzone-display-session";
+const VERSION: u32 = 3;
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        encode(&mut writer, &(MAGIC, VERSION))?;
+        let start = Instant::now();
+        Ok(Self { writer, start })
+    }
+
+    /// Record a token, timestamped in seconds since the session start.
+    pub fn record(&mut self, token: &Token) -> io::Result<()> {
+        let time = self.start.elapsed().as_secs_f64();
+        encode(&mut self.writer, &(time, token))
+    }
+}
+
+pub struct Player {
+    reader: BufReader<File>,
+}
+
+impl Player {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let header: Option<(String, u32)> = decode(&mut reader)
+            .map_err(|_| bad_session("missing header"))?;
+        match header {
+            Some((magic, version)) if magic == MAGIC => {
+                if version != VERSION {
+                    let msg = format!("expected version {}, found {}", VERSION, version);
+                    return Err(bad_session(&msg))
+                }
+            },
+            _ => return Err(bad_session("missing header")),
+        }
+        Ok(Self { reader })
+    }
+}
+
+impl Iterator for Player {
+    type Item = io::Result<(f64, Token)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        decode(&mut self.reader).transpose()
+    }
+}
+
+fn bad_session(reason: &str) -> io::Error {
+    let msg = format!("bad session ({})", reason);
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
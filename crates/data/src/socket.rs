@@ -0,0 +1,201 @@
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use super::ipc::{decode, encode, Token};
+
+
+// ===============================================================================================
+//
+// Socket address.
+//
+// ===============================================================================================
+
+/// A display address, either `tcp:HOST:PORT` (the default) or `unix:PATH`.
+#[derive(Clone, Debug)]
+pub enum Address {
+    Tcp(String),
+    Unix(PathBuf),
+}
+
+impl FromStr for Address {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = if let Some(path) = s.strip_prefix("unix:") {
+            Self::Unix(PathBuf::from(path))
+        } else {
+            let host = s.strip_prefix("tcp:").unwrap_or(s);
+            Self::Tcp(host.to_string())
+        };
+        if address.is_empty() {
+            let msg = format!("bad address (found '{}')", s);
+            Err(io::Error::new(ErrorKind::InvalidInput, msg))
+        } else {
+            Ok(address)
+        }
+    }
+}
+
+impl Address {
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Tcp(host) => host.is_empty(),
+            Self::Unix(path) => path.as_os_str().is_empty(),
+        }
+    }
+}
+
+
+// ===============================================================================================
+//
+// Socket stream.
+//
+// ===============================================================================================
+
+pub enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    pub fn connect(address: &Address) -> io::Result<Self> {
+        match address {
+            Address::Tcp(host) => {
+                let stream = TcpStream::connect(host.as_str())?;
+                stream.set_nodelay(true)?;
+                Ok(Self::Tcp(stream))
+            },
+            #[cfg(unix)]
+            Address::Unix(path) => Ok(Self::Unix(UnixStream::connect(path)?)),
+            #[cfg(not(unix))]
+            Address::Unix(_) => Err(unsupported()),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+
+// ===============================================================================================
+//
+// Socket listener (agent side).
+//
+// ===============================================================================================
+
+pub enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+impl Listener {
+    pub fn bind(address: &Address) -> io::Result<Self> {
+        match address {
+            Address::Tcp(host) => Ok(Self::Tcp(TcpListener::bind(host.as_str())?)),
+            #[cfg(unix)]
+            Address::Unix(path) => {
+                if path.exists() && UnixStream::connect(path).is_err() {
+                    std::fs::remove_file(path)?; // Stale socket, e.g. from a killed agent.
+                }
+                let listener = UnixListener::bind(path)?;
+                Ok(Self::Unix(listener, path.clone()))
+            },
+            #[cfg(not(unix))]
+            Address::Unix(_) => Err(unsupported()),
+        }
+    }
+
+    pub fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Self::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                Ok(Stream::Tcp(stream))
+            },
+            #[cfg(unix)]
+            Self::Unix(listener, _) => {
+                let (stream, _) = listener.accept()?;
+                Ok(Stream::Unix(stream))
+            },
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Self::Unix(_, path) = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn unsupported() -> io::Error {
+    io::Error::new(ErrorKind::Unsupported, "unix sockets are not supported on this platform")
+}
+
+
+// ===============================================================================================
+//
+// Tokens transport.
+//
+// ===============================================================================================
+
+pub struct Sender (BufWriter<Stream>);
+
+impl Sender {
+    pub fn connect(address: &Address) -> io::Result<Self> {
+        let stream = Stream::connect(address)?;
+        Ok(Self(BufWriter::new(stream)))
+    }
+
+    pub fn send(&mut self, token: &Token) -> io::Result<()> {
+        encode(&mut self.0, token)
+    }
+}
+
+pub struct Receiver (BufReader<Stream>);
+
+impl Receiver {
+    pub fn new(stream: Stream) -> Self {
+        Self(BufReader::new(stream))
+    }
+
+    /// Receive the next token, or `None` if the peer closed the connection.
+    pub fn recv(&mut self) -> io::Result<Option<Token>> {
+        decode(&mut self.0)
+    }
+}
@@ -10,10 +10,13 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use super::display::DisplayPlugin;
 use super::drone::DronePlugin;
 use super::event::EventPlugin;
+use super::export::ExportPlugin;
 use super::geometry::GeometryPlugin;
 use super::lighting::LightingPlugin;
 use super::ui::UiPlugin;
 
+pub use super::drone::{on_camera, set_camera};
+
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub(crate) enum AppState {
     Display,
@@ -88,6 +91,7 @@ pub fn run() -> u8 {
             DisplayPlugin,
             DronePlugin,
             EventPlugin,
+            ExportPlugin,
             GeometryPlugin,
             LightingPlugin,
             UiPlugin,
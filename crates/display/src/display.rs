@@ -1,10 +1,13 @@
 use bevy::prelude::*;
 use bevy::pbr::wireframe::Wireframe;
 use crate::app::AppState;
-use crate::geometry::{Plain, Transparent, Volume};
+use crate::geometry::{Plain, Transparent, Volume, pick_volume, update_visibility};
 use crate::lighting::{Shadows, Sun};
 use crate::ui::{TextInputSet, TextInputState};
 
+mod cutaway;
+
+use cutaway::{Cutaway, Occluding};
 
 pub struct DisplayPlugin;
 
@@ -45,11 +48,16 @@ impl Plugin for DisplayPlugin {
             .init_resource::<WireframeMode>()
             .init_resource::<BlendSettings>()
             .init_resource::<PremultipliedSettings>()
+            .init_resource::<Cutaway>()
             .add_systems(Update, (
-                on_keyboard
+                (on_keyboard, cutaway::on_keyboard)
                     .after(TextInputSet)
                     .run_if(in_state(TextInputState::Inactive)),
                 (on_display_mode, on_wireframe_mode).after(on_keyboard),
+                cutaway::update_cutaway
+                    .after(cutaway::on_keyboard)
+                    .after(pick_volume)
+                    .before(update_visibility),
             ).run_if(in_state(AppState::Display)));
     }
 }
@@ -105,7 +113,7 @@ fn on_display_mode(
     mode: Res<DisplayMode>,
     blend_settings: Res<BlendSettings>,
     premultiplied_settings: Res<PremultipliedSettings>,
-    handles: Query<&MeshMaterial3d<StandardMaterial>, With<Volume>>,
+    handles: Query<(&MeshMaterial3d<StandardMaterial>, Option<&Occluding>), With<Volume>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
     sun: Res<Sun>,
@@ -120,7 +128,7 @@ fn on_display_mode(
 
     match *mode {
         DisplayMode::Blend => {
-            for handle in handles.iter() {
+            for handle in originals(&handles) {
                 let material = materials.get_mut(handle).unwrap();
                 material.alpha_mode = AlphaMode::Blend;
                 material.base_color.set_alpha(blend_settings.alpha);
@@ -128,7 +136,7 @@ fn on_display_mode(
             Shadows::disable(&mut commands, &sun);
         },
         DisplayMode::Opaque => {
-            for handle in handles.iter() {
+            for handle in originals(&handles) {
                 let material = materials.get_mut(handle).unwrap();
                 material.alpha_mode = AlphaMode::Opaque;
                 material.base_color.set_alpha(1.0);
@@ -136,7 +144,7 @@ fn on_display_mode(
             Shadows::enable(&mut commands, &sun);
         },
         DisplayMode::Premultiplied => {
-            for handle in handles.iter() {
+            for handle in originals(&handles) {
                 let material = materials.get_mut(handle).unwrap();
                 material.alpha_mode = AlphaMode::Premultiplied;
                 material.base_color.set_alpha(premultiplied_settings.alpha);
@@ -147,6 +155,15 @@ fn on_display_mode(
     }
 }
 
+/// Material handles of volumes, ignoring cutaway ones.
+fn originals<'a>(
+    handles: &'a Query<(&MeshMaterial3d<StandardMaterial>, Option<&Occluding>), With<Volume>>,
+) -> impl Iterator<Item=&'a Handle<StandardMaterial>> {
+    handles
+        .iter()
+        .map(|(handle, occluding)| occluding.map(|occluding| &occluding.0).unwrap_or(&handle.0))
+}
+
 fn on_wireframe_mode(
     mode: Res<WireframeMode>,
     standard_entities: Query<Entity, With<Plain>>,
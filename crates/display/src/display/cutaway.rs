@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::drone::DroneCamera;
+use crate::event::Vertex;
+use crate::geometry::{Plain, Selection, Volume};
+use std::collections::HashSet;
+
+
+/// Cutaway of the volumes lying between the camera and a target, i.e. the picked volume or,
+/// otherwise, the displayed event vertices.
+#[derive(Clone, Copy, Default, PartialEq, Resource)]
+#[repr(i32)]
+pub(crate) enum Cutaway {
+    #[default]
+    Disabled,
+    Transparent,
+    Hidden,
+    Guard,
+}
+
+/// The original material of a volume made transparent by the cutaway.
+#[derive(Component)]
+pub(crate) struct Occluding(pub Handle<StandardMaterial>);
+
+/// Opacity of cut away volumes, in transparent mode.
+const CUTAWAY_ALPHA: f32 = 0.15;
+
+type Volumes<'w, 's> = Query<'w, 's, (
+    Entity,
+    &'static mut Volume,
+    Has<Plain>,
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Option<&'static Occluding>,
+)>;
+
+pub(crate) fn on_keyboard(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut cutaway: ResMut<Cutaway>,
+) {
+    if keys.just_pressed(KeyCode::KeyX) {
+        *cutaway = cutaway.next();
+    }
+}
+
+/// Cut away occluding volumes when the camera, the target or the mode change.
+pub(crate) fn update_cutaway(
+    cutaway: Res<Cutaway>,
+    selection: Res<Selection>,
+    camera: Query<Ref<GlobalTransform>, With<DroneCamera>>,
+    vertices: Query<&GlobalTransform, With<Vertex>>,
+    added: Query<(), Added<Vertex>>,
+    mut removed: RemovedComponents<Vertex>,
+    rapier: ReadRapierContext,
+    descendants: Query<&Children>,
+    mut volumes: Volumes,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let Ok(camera) = camera.single() else { return };
+    let removed = removed.read().count() > 0;
+    if !(cutaway.is_changed() || selection.is_changed() || camera.is_changed() ||
+         removed || !added.is_empty()) {
+        return
+    }
+
+    let occluders = match *cutaway {
+        Cutaway::Disabled => HashSet::new(),
+        _ => {
+            let target = selection.0.and_then(|entity| {
+                let (_, volume, ..) = volumes.get(entity).ok()?;
+                let min = Vec3::from(volume.aabb.min());
+                let max = Vec3::from(volume.aabb.max());
+                Some((min, max))
+            });
+            let target = target.or_else(|| {
+                let mut positions = vertices.iter().map(|vertex| vertex.translation());
+                let first = positions.next()?;
+                Some(positions.fold((first, first), |(min, max), p| (min.min(p), max.max(p))))
+            });
+            match target {
+                Some((min, max)) => {
+                    let mut excluded: HashSet<Entity> = HashSet::new();
+                    if let Some(entity) = selection.0 {
+                        excluded.insert(entity);
+                        excluded.extend(descendants.iter_descendants(entity));
+                    }
+                    find_occluders(&rapier, camera.translation(), min, max, &excluded, &volumes)
+                },
+                None => HashSet::new(),
+            }
+        },
+    };
+
+    for (entity, mut volume, _, material, occluding) in volumes.iter_mut() {
+        let occluder = occluders.contains(&entity);
+        let cut = occluder && (*cutaway == Cutaway::Hidden);
+        if volume.cut != cut {
+            volume.cut = cut;
+        }
+        let transparent = occluder && (*cutaway == Cutaway::Transparent);
+        match (transparent, occluding, material) {
+            (true, None, Some(material)) => {
+                let Some(original) = materials.get(material) else { continue };
+                let mut clone = original.clone();
+                clone.alpha_mode = AlphaMode::Blend;
+                clone.base_color.set_alpha(CUTAWAY_ALPHA);
+                let clone = materials.add(clone);
+                commands
+                    .entity(entity)
+                    .insert((MeshMaterial3d(clone), Occluding(material.0.clone())));
+            },
+            (false, Some(occluding), _) => {
+                commands
+                    .entity(entity)
+                    .insert(MeshMaterial3d(occluding.0.clone()))
+                    .remove::<Occluding>();
+            },
+            _ => (),
+        }
+    }
+}
+
+/// Collect opaque volumes crossed by rays from the camera to the target box.
+fn find_occluders(
+    rapier: &ReadRapierContext,
+    origin: Vec3,
+    min: Vec3,
+    max: Vec3,
+    excluded: &HashSet<Entity>,
+    volumes: &Volumes,
+) -> HashSet<Entity> {
+    let mut occluders = HashSet::new();
+    let Ok(context) = rapier.single() else { return occluders };
+
+    // Aim at the centre of the target, and at inner corners of its bounding box.
+    let center = 0.5 * (min + max);
+    let half = 0.25 * (max - min);
+    let mut points = vec![center];
+    for i in 0..8 {
+        let sign = Vec3::new(
+            if (i & 1) == 0 { -1.0 } else { 1.0 },
+            if (i & 2) == 0 { -1.0 } else { 1.0 },
+            if (i & 4) == 0 { -1.0 } else { 1.0 },
+        );
+        points.push(center + sign * half);
+    }
+
+    // Volumes hidden by the user, or clipped, do not occlude. Cut volumes still do, in order to
+    // avoid flickering.
+    let predicate = |entity: Entity| !excluded.contains(&entity) && volumes
+        .get(entity)
+        .is_ok_and(|(_, volume, plain, ..)| plain && volume.visible && !volume.clipped);
+    let filter = QueryFilter::default().predicate(&predicate);
+    for point in points {
+        let direction = point - origin;
+        let distance = direction.length();
+        if distance <= 0.0 {
+            continue
+        }
+        context.intersections_with_ray(
+            origin, direction / distance, distance, false, filter,
+            |entity, _| {
+                occluders.insert(entity);
+                true
+            },
+        );
+    }
+    occluders
+}
+
+impl Cutaway {
+    fn next(self) -> Self {
+        ((self as i32) + 1)
+            .rem_euclid(Self::Guard as i32)
+            .into()
+    }
+}
+
+impl From<i32> for Cutaway {
+    fn from(value: i32) -> Self {
+        if value == (Self::Disabled as i32) {
+            Self::Disabled
+        } else if value == (Self::Transparent as i32) {
+            Self::Transparent
+        } else if value == (Self::Hidden as i32) {
+            Self::Hidden
+        } else {
+            unreachable!()
+        }
+    }
+}
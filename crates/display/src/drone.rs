@@ -8,6 +8,8 @@ use crate::app::{AppState, Removable};
 use crate::event::{EventBundle, EventCamera};
 use crate::geometry::{GeometrySet, RootVolume, Volume};
 use crate::ui::{Meters, TextInputSet, TextInputState, UiRoot};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 
 pub struct DronePlugin;
@@ -25,11 +27,32 @@ impl Plugin for DronePlugin {
                     .after(TextInputSet)
                     .run_if(in_state(TextInputState::Inactive)),
                 on_target,
+                update_camera.after(on_target),
                 on_transform,
+                report_camera.after(update_camera),
             ).run_if(in_state(AppState::Display)));
     }
 }
 
+static CAMERA: Mutex<Option<data::geometry::Camera>> = Mutex::new(None);
+
+type CameraListener = Box<dyn FnMut(data::geometry::Camera) + Send>;
+
+static CAMERA_LISTENER: Mutex<Option<CameraListener>> = Mutex::new(None);
+
+/// Move the drone camera, e.g. when replaying a session.
+pub fn set_camera(camera: data::geometry::Camera) {
+    *CAMERA.lock().unwrap() = Some(camera);
+}
+
+/// Listen to drone camera changes, e.g. for recording sessions.
+pub fn on_camera<F>(listener: F)
+where
+    F: FnMut(data::geometry::Camera) + Send + 'static,
+{
+    *CAMERA_LISTENER.lock().unwrap() = Some(Box::new(listener));
+}
+
 #[derive(Component)]
 pub struct Drone {
     velocity: f32,
@@ -289,6 +312,64 @@ fn on_target(
     Ok(())
 }
 
+/// Apply any pending camera, once the drone exists.
+fn update_camera(
+    mut drone: Query<(Entity, &Drone, &mut Transform, &mut Velocity)>,
+    mut drone_camera: Query<&mut Projection, (
+        With<DroneCamera>, Without<EventCamera>,
+    )>,
+    event_camera: Query<&mut Projection, (
+        With<EventCamera>, Without<DroneCamera>,
+    )>,
+    mut commands: Commands,
+) -> Result<()> {
+    let Ok((entity, drone, mut transform, mut velocity)) = drone.single_mut() else {
+        return Ok(())
+    };
+    let Some(camera) = CAMERA.lock().unwrap().take() else { return Ok(()) };
+    commands.entity(entity).insert(RigidBodyDisabled); // Disable rapier phys. before warping.
+    transform.translation = Vec3::from(camera.translation);
+    transform.rotation = Quat::from_array(camera.rotation).normalize();
+    velocity.linvel = Vec3::ZERO;
+    if let Projection::Perspective(perspective) = drone_camera.single_mut()?.into_inner() {
+        perspective.fov = camera.fov.clamp(Drone::FOV_MIN, Drone::FOV_MAX);
+        update_zoom(drone, perspective, event_camera, &mut commands)?;
+    }
+    Ok(())
+}
+
+/// Report drone camera changes to the listener, at most every 100 ms.
+///
+/// The last change is kept pending, such that the final position is reported once the drone
+/// stops.
+fn report_camera(
+    drone: Query<Ref<Transform>, With<Drone>>,
+    drone_camera: Query<Ref<Projection>, With<DroneCamera>>,
+    mut pending: Local<Option<data::geometry::Camera>>,
+    mut reported: Local<Option<Instant>>,
+) {
+    const PERIOD: Duration = Duration::from_millis(100);
+
+    if let (Ok(transform), Ok(projection)) = (drone.single(), drone_camera.single()) &&
+        (transform.is_changed() || projection.is_changed()) &&
+        let Projection::Perspective(perspective) = projection.into_inner() {
+        *pending = Some(data::geometry::Camera {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+            fov: perspective.fov,
+        });
+    }
+    let now = Instant::now();
+    if reported.is_some_and(|reported| now < reported + PERIOD) {
+        return
+    }
+    let Some(camera) = pending.take() else { return };
+    if let Some(listener) = CAMERA_LISTENER.lock().unwrap().as_mut() {
+        listener(camera);
+    }
+    *reported = Some(now);
+}
+
 fn on_transform(
     mut commands: Commands,
     query: Query<(&Drone, &Transform), Changed<Transform>>,
@@ -6,19 +6,32 @@ use bevy::window::PrimaryWindow;
 use bevy_polyline::prelude::*;
 use crate::app::{AppState, Removable};
 use crate::drone::Drone;
+use crate::geometry::{Clipping, update_clipping};
 use crate::ui::{PrimaryMenu, TextInputSet, TextInputState, UiEvent};
-use std::borrow::Cow;
 
 mod colours;
 mod data;
+mod filter;
+mod overlay;
+mod pdg;
 mod picking;
+mod voxels;
 
-pub use data::set;
+pub use data::{set, set_columns};
+pub use filter::set_filter;
+pub use overlay::set_overlay;
+pub use voxels::set_voxels;
 
 pub(crate) use data::Events as EventsData;
 pub(crate) use data::Event as EventData;
 pub(crate) use data::Target;
 pub(crate) use data::Track as TrackData;
+pub(crate) use colours::{Coloring, Legend};
+pub(crate) use filter::Filter;
+pub(crate) use overlay::Overlay;
+pub(crate) use pdg::Particle;
+pub(crate) use picking::Pick;
+pub(crate) use voxels::Voxels;
 use data::ToView;
 
 
@@ -30,12 +43,31 @@ impl Plugin for EventPlugin {
             .add_plugins(PolylinePlugin)
             .add_plugins(picking::PickingPlugin)
             .init_resource::<Events>()
+            .init_resource::<Filter>()
+            .init_resource::<Coloring>()
+            .init_resource::<Legend>()
+            .init_resource::<Overlay>()
+            .init_resource::<Voxels>()
+            .add_event::<EventsUpdate>()
             .add_systems(Update, (
                     update_events,
-                    draw_event,
+                    filter::update_filter,
+                    overlay::update_overlay,
+                    load_event
+                        .after(update_events)
+                        .after(overlay::update_overlay)
+                        .after(on_keyboard),
+                    draw_event
+                        .after(load_event)
+                        .after(filter::update_filter)
+                        .after(update_clipping),
                     on_keyboard
                         .after(TextInputSet)
                         .run_if(in_state(TextInputState::Inactive)),
+                    voxels::update_voxels,
+                    voxels::draw_voxels
+                        .after(update_events)
+                        .after(voxels::update_voxels),
                 ).run_if(in_state(AppState::Display))
             );
     }
@@ -44,7 +76,35 @@ impl Plugin for EventPlugin {
 #[derive(Default, Resource)]
 pub(crate) struct Events {
     pub data: EventsData,
+    /// Id of the current event.
     pub index: usize,
+    /// Sorted events ids.
+    pub keys: Vec<usize>,
+}
+
+impl Events {
+    /// Step through events ids, wrapping around if `wrap` is set, or clamping otherwise.
+    pub fn step(&mut self, delta: isize, wrap: bool) {
+        let n = self.keys.len() as isize;
+        if n == 0 {
+            return
+        }
+        let position = match self.keys.binary_search(&self.index) {
+            Ok(position) => position as isize,
+            Err(position) => if delta > 0 { position as isize - 1 } else { position as isize },
+        };
+        let position = if wrap {
+            (position + delta).rem_euclid(n)
+        } else {
+            (position + delta).clamp(0, n - 1)
+        };
+        self.index = self.keys[position as usize];
+    }
+
+    /// Check if there is an event with the given id.
+    pub fn contains(&self, index: usize) -> bool {
+        self.keys.binary_search(&index).is_ok()
+    }
 }
 
 #[derive(Component)]
@@ -52,13 +112,20 @@ pub(crate) struct Event;
 
 #[derive(Component)]
 pub(crate) struct Track {
+    pub event: usize,
     pub tid: i32,
     pub parent: i32,
     pub pid: i32,
     pub creator: String,
 }
 
+/// An unclipped piece of a track, starting at its `first` segment.
 #[derive(Component)]
+pub(crate) struct TrackPiece {
+    pub first: usize,
+}
+
+#[derive(Clone, Component)]
 pub(crate) struct Vertex {
     pub energy: f32,
     pub process: String,
@@ -68,12 +135,28 @@ pub(crate) struct Vertex {
 #[derive(Component)]
 struct VertexSize (f32);
 
-fn update_events(mut events: ResMut<Events>) {
+/// Notification of new events data.
+#[derive(Event)]
+pub(crate) struct EventsUpdate;
+
+fn update_events(mut events: ResMut<Events>, mut ev_update: EventWriter<EventsUpdate>) {
     if let Some(data) = data::take() {
-        *events = Events {
-            data,
-            index: 0,
+        let keys = data.keys();
+        let index = keys.first().copied().unwrap_or(0);
+        *events = Events { data, index, keys };
+        ev_update.write(EventsUpdate);
+    }
+}
+
+/// Reconstruct the displayed events, if needed, without triggering change detection.
+pub(crate) fn load_event(mut events: ResMut<Events>, overlay: Res<Overlay>) {
+    if events.is_changed() || overlay.is_changed() {
+        let events = events.bypass_change_detection();
+        let indices = overlay.indices(events);
+        for index in indices.iter() {
+            events.data.load(*index);
         }
+        events.data.evict(&indices);
     }
 }
 
@@ -81,6 +164,10 @@ const EVENT_LAYER: usize = 1;
 
 fn draw_event(
     events: Res<Events>,
+    filter: Res<Filter>,
+    coloring: Res<Coloring>,
+    overlay: Res<Overlay>,
+    clipping: Res<Clipping>,
     current_event: Query<Entity, With<Event>>,
     primary_menu: Query<Entity, With<PrimaryMenu>>,
     primary_window: Query<&Window, With<PrimaryWindow>>,
@@ -92,108 +179,223 @@ fn draw_event(
 ) {
     let Ok(primary_window) = primary_window.single() else { return };
 
-    if events.is_changed() && (events.index < events.data.0.len()) {
-        if let Some(event) = events.data.0.get(&events.index) {
-            // Remove any existing event.
-            for entity in current_event.iter() {
-                commands
-                    .entity(entity)
-                    .despawn();
-            }
-
-            // Spawn the current event.
-            commands
-                .spawn((
-                    Event,
-                    Transform::default(),
-                    Visibility::default(),
-                    Removable,
-                ))
-                .with_children(|parent| {
-                    for track in event.tracks.values() {
-                        let vertex_size = match track.pid {
-                            22 => 5E-04,
-                            _ => 3E-04,
-                        };
-                        let vertex_mesh = Sphere::new(vertex_size).mesh().build();
-                        let vertex_mesh = meshes.add(vertex_mesh);
-                        let color = match colours::COLOURS.get(&track.pid) {
-                            Some(color) => *color,
-                            None => LinearRgba::WHITE,
-                        };
-                        let vertex_material = StandardMaterial {
-                            base_color: color.into(),
-                            unlit: true,
-                            ..default()
-                        };
-                        let vertex_material = materials.add(vertex_material);
-                        let vertices: Vec<Vec3> = track.vertices
-                            .iter()
-                            .map(|v| v.position.to_view())
-                            .collect();
-                        let polyline = Polyline { vertices };
-                        let material = PolylineMaterial {
-                            width: 1.0,
-                            color,
-                            ..default()
-                        };
-                        parent
-                            .spawn((
-                                Track::from(track),
-                                PolylineBundle {
-                                    polyline: PolylineHandle(polylines.add(polyline)),
-                                    material: PolylineMaterialHandle(polymats.add(material)),
-                                    ..default()
-                                },
-                                RenderLayers::layer(EVENT_LAYER),
-                            ))
-                            .with_children(|parent| {
-                                let n = track.vertices.len();
-                                for vertex in track.vertices[0..n].iter() {
-                                    parent.spawn((
-                                        Vertex::from(vertex),
-                                        VertexSize(vertex_size),
-                                        MeshMaterial3d(vertex_material.clone()),
-                                        Mesh3d(vertex_mesh.clone()),
-                                        Transform::from_translation(vertex.position.to_view()),
-                                        RenderLayers::layer(EVENT_LAYER),
-                                    ));
-                                }
-                          });
+    let changed = events.is_changed() || filter.is_changed() || coloring.is_changed() ||
+        overlay.is_changed() || clipping.is_changed();
+    if !changed || events.data.get(&events.index).is_none() {
+        return
+    }
+
+    // Remove any existing event.
+    for entity in current_event.iter() {
+        commands
+            .entity(entity)
+            .despawn();
+    }
+
+    // Collect the displayed events, e.g. overlaid ones.
+    let displayed: Vec<(usize, &EventData)> = overlay
+        .indices(&events)
+        .into_iter()
+        .filter_map(|index| events.data.get(&index).map(|event| (index, event)))
+        .collect();
+    let colours = colours::Colours::new(*coloring, &displayed, &filter);
+
+    // Colour vertices by density, if requested.
+    const DENSITY_LEVELS: usize = 16;
+    let density = overlay.density().then(|| {
+        let positions: Vec<Vec3> = displayed
+            .iter()
+            .flat_map(|(_, event)| event.tracks.values())
+            .filter(|track| filter.accepts_track(track))
+            .flat_map(|track| track.vertices.iter())
+            .filter(|vertex| filter.accepts_vertex(vertex))
+            .map(|vertex| vertex.position.to_view())
+            .collect();
+        let levels = overlay::density(&positions);
+        let materials: Vec<_> = (0..DENSITY_LEVELS)
+            .map(|i| materials.add(StandardMaterial {
+                base_color: colours::gradient(i as f32 / (DENSITY_LEVELS - 1) as f32).into(),
+                unlit: true,
+                ..default()
+            }))
+            .collect();
+        (levels, materials)
+    });
+    let mut vertex_index = 0;
+
+    // Spawn the displayed events.
+    let vertex_meshes = [3E-04, 5E-04].map(|size| {
+        (size, meshes.add(Sphere::new(size).mesh().build()))
+    });
+    for (index, event) in displayed.iter() {
+        commands
+            .spawn((
+                Event,
+                Transform::default(),
+                Visibility::default(),
+                Removable,
+            ))
+            .with_children(|parent| {
+                for track in event.tracks.values() {
+                    if !filter.accepts_track(track) {
+                        continue
                     }
-                });
-            UiEvent::spawn_status(&events, primary_menu, &primary_window, &mut commands);
-        }
+                    let (vertex_size, vertex_mesh) = match track.pid {
+                        22 => vertex_meshes[1].clone(),
+                        _ => vertex_meshes[0].clone(),
+                    };
+                    let color = colours.get(*index, track.tid);
+                    let vertex_material = StandardMaterial {
+                        base_color: color.into(),
+                        unlit: true,
+                        ..default()
+                    };
+                    let vertex_material = materials.add(vertex_material);
+                    let vertices: Vec<Vec3> = track.vertices
+                        .iter()
+                        .map(|v| v.position.to_view())
+                        .collect();
+                    // Clipped tracks are drawn by pieces.
+                    let pieces = clipping.tracks().then(|| clipping.clip_polyline(&vertices));
+                    let polyline = match pieces {
+                        Some(_) => Polyline { vertices: Vec::new() },
+                        None => Polyline { vertices },
+                    };
+                    let material = PolylineMaterial {
+                        width: 1.0,
+                        color,
+                        ..default()
+                    };
+                    let material = polymats.add(material);
+                    parent
+                        .spawn((
+                            Track::new(*index, track),
+                            PolylineBundle {
+                                polyline: PolylineHandle(polylines.add(polyline)),
+                                material: PolylineMaterialHandle(material.clone()),
+                                ..default()
+                            },
+                            RenderLayers::layer(EVENT_LAYER),
+                        ))
+                        .with_children(|parent| {
+                            for (first, vertices) in pieces.into_iter().flatten() {
+                                parent.spawn((
+                                    TrackPiece { first },
+                                    PolylineBundle {
+                                        polyline: PolylineHandle(
+                                            polylines.add(Polyline { vertices })
+                                        ),
+                                        material: PolylineMaterialHandle(material.clone()),
+                                        ..default()
+                                    },
+                                    RenderLayers::layer(EVENT_LAYER),
+                                ));
+                            }
+                            for vertex in track.vertices.iter() {
+                                if !filter.accepts_vertex(vertex) {
+                                    continue
+                                }
+                                let material = match density.as_ref() {
+                                    Some((levels, materials)) => {
+                                        let level = levels[vertex_index];
+                                        vertex_index += 1;
+                                        let level = (level * (DENSITY_LEVELS - 1) as f32)
+                                            .round() as usize;
+                                        materials[level].clone()
+                                    },
+                                    None => vertex_material.clone(),
+                                };
+                                let position = vertex.position.to_view();
+                                if clipping.tracks() && !clipping.contains(position) {
+                                    continue
+                                }
+                                parent.spawn((
+                                    Vertex::from(vertex),
+                                    VertexSize(vertex_size),
+                                    MeshMaterial3d(material),
+                                    Mesh3d(vertex_mesh.clone()),
+                                    Transform::from_translation(position),
+                                    RenderLayers::layer(EVENT_LAYER),
+                                ));
+                            }
+                      });
+                }
+            });
     }
+    commands.insert_resource(colours.legend);
+    UiEvent::spawn_status(&events, primary_menu, &primary_window, &mut commands);
 }
 
 fn on_keyboard(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut events: ResMut<Events>,
+    mut coloring: ResMut<Coloring>,
+    overlay: Res<Overlay>,
+    voxels: Res<Voxels>,
 ) {
-    let n = events.data.0.len();
-    if n == 0 {
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keyboard_input.just_pressed(KeyCode::F2) {
+        *coloring = coloring.next();
+    }
+    if keyboard_input.just_pressed(KeyCode::F8) {
+        // Toggle the overlay of all events, or their vertices density if shifted.
+        let new_overlay = if shift {
+            let mut new_overlay = overlay.0.clone().unwrap_or_default();
+            new_overlay.density = !overlay.density();
+            Some(new_overlay)
+        } else if overlay.is_some() {
+            None
+        } else {
+            Some(data::Overlay::default())
+        };
+        set_overlay(new_overlay);
+    }
+    if keyboard_input.just_pressed(KeyCode::F9) {
+        // Toggle the vertices density grid, or its energy weighting if shifted.
+        let new_voxels = if shift {
+            let mut new_voxels = voxels.0.clone().unwrap_or_default();
+            new_voxels.energy = !new_voxels.energy;
+            Some(new_voxels)
+        } else if voxels.is_some() {
+            None
+        } else {
+            Some(data::Voxels::default())
+        };
+        set_voxels(new_voxels);
+    }
+
+    if events.keys.is_empty() {
         return;
     }
 
+    // Shift+Arrow jumps by ten events, since PageUp and PageDown are taken by the display mode.
+    const JUMP: isize = 10;
     if keyboard_input.just_pressed(KeyCode::ArrowRight) {
-        events.index += 1;
-        if events.index >= n {
-            events.index = 0;
+        if shift {
+            events.step(JUMP, false);
+        } else {
+            events.step(1, true);
         }
     }
     if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
-        if events.index > 0 {
-            events.index -= 1;
+        if shift {
+            events.step(-JUMP, false);
         } else {
-            events.index = n - 1;
+            events.step(-1, true);
         }
     }
+    if keyboard_input.just_pressed(KeyCode::Home) {
+        events.index = events.keys[0];
+    }
+    if keyboard_input.just_pressed(KeyCode::End) {
+        events.index = events.keys[events.keys.len() - 1];
+    }
 }
 
-impl<'a> From<&'a data::Track> for Track {
-    fn from(track: &'a data::Track) -> Self {
+impl Track {
+    fn new(event: usize, track: &data::Track) -> Self {
         Self {
+            event,
             tid: track.tid,
             parent: track.parent,
             pid: track.pid,
@@ -238,7 +440,7 @@ impl EventBundle {
                 near: Drone::NEAR,
                 ..default()
             }),
-            RenderLayers::layer(EVENT_LAYER),
+            RenderLayers::from_layers(&[EVENT_LAYER, voxels::VOXELS_LAYER]),
         )
     }
 }
@@ -249,17 +451,9 @@ impl Track {
     }
 
     pub fn label_from_parts(tid: i32, pid: i32) -> String {
-        let particle = match pid {
-            11 => Cow::Borrowed("e-"),
-            -11 => Cow::Borrowed("e+"),
-            13 => Cow::Borrowed("mu-"),
-            -13 => Cow::Borrowed("mu+"),
-            22 => Cow::Borrowed("gamma"),
-            _ => Cow::Owned(format!("[{}]", pid)),
-        };
         format!(
             "{} [{}]",
-            particle,
+            Particle::name(pid),
             tid,
         )
     }
@@ -1,7 +1,8 @@
-use bevy::color::LinearRgba;
+use bevy::prelude::*;
 use bevy::color::palettes::css;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::LazyLock;
+use super::{EventData, Filter, Particle};
 
 
 pub static COLOURS: LazyLock<HashMap<i32, LinearRgba>> = LazyLock::new(|| HashMap::from([
@@ -10,4 +11,280 @@ pub static COLOURS: LazyLock<HashMap<i32, LinearRgba>> = LazyLock::new(|| HashMa
     ( 13,  LinearRgba::from(css::DARK_GREEN)),
     (-13,  LinearRgba::from(css::FOREST_GREEN)),
     ( 22,  LinearRgba::from(css::GOLD)),
+    ( 211, LinearRgba::from(css::CRIMSON)),
+    (-211, LinearRgba::from(css::HOT_PINK)),
+    ( 111, LinearRgba::from(css::ORANGE)),
+    ( 2212, LinearRgba::from(css::DARK_RED)),
+    ( 2112, LinearRgba::from(css::SLATE_GRAY)),
+    ( 321, LinearRgba::from(css::PURPLE)),
+    (-321, LinearRgba::from(css::ORCHID)),
+    ( 12, LinearRgba::from(css::LIGHT_GRAY)),
+    (-12, LinearRgba::from(css::LIGHT_GRAY)),
+    ( 14, LinearRgba::from(css::LIGHT_GRAY)),
+    (-14, LinearRgba::from(css::LIGHT_GRAY)),
 ]));
+
+/// Categorical palette, for particles without a predefined colour, creators, etc.
+const PALETTE: [Srgba; 12] = [
+    css::TOMATO, css::TEAL, css::GOLDENROD, css::SLATE_BLUE, css::OLIVE_DRAB, css::SIENNA,
+    css::DEEP_PINK, css::STEEL_BLUE, css::DARK_ORANGE, css::MEDIUM_SEA_GREEN, css::INDIGO,
+    css::ROSY_BROWN,
+];
+
+/// Colour map for continuous quantities, from low to high values.
+const GRADIENT: [Srgba; 5] = [css::BLUE, css::DEEP_SKY_BLUE, css::LIME, css::YELLOW, css::RED];
+
+
+// ===============================================================================================
+//
+// Tracks coloring modes.
+//
+// ===============================================================================================
+
+#[derive(Clone, Copy, Default, PartialEq, Resource)]
+pub(crate) enum Coloring {
+    #[default]
+    Particle,
+    Energy,
+    Time,
+    Generation,
+    Creator,
+    Charge,
+    Event,
+}
+
+impl Coloring {
+    const ALL: [Self; 7] = [
+        Self::Particle, Self::Energy, Self::Time, Self::Generation, Self::Creator, Self::Charge,
+        Self::Event,
+    ];
+
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|mode| mode == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Particle => "particle",
+            Self::Energy => "energy",
+            Self::Time => "time",
+            Self::Generation => "generation",
+            Self::Creator => "creator",
+            Self::Charge => "charge",
+            Self::Event => "event",
+        }
+    }
+}
+
+/// Legend of the current coloring, as labelled colours.
+#[derive(Default, Resource)]
+pub(crate) struct Legend {
+    pub title: String,
+    pub entries: Vec<(String, LinearRgba)>,
+}
+
+/// Colours of the tracks of (overlaid) events, indexed by event id and track id.
+pub(crate) struct Colours {
+    tracks: HashMap<(usize, i32), LinearRgba>,
+    pub legend: Legend,
+}
+
+impl Colours {
+    pub fn new(coloring: Coloring, events: &[(usize, &EventData)], filter: &Filter) -> Self {
+        let mut tracks: Vec<_> = events
+            .iter()
+            .flat_map(|(index, event)| event.tracks
+                .values()
+                .filter(|track| filter.accepts_track(track))
+                .map(move |track| (*index, track))
+            )
+            .collect();
+        tracks.sort_by_key(|(index, track)| (*index, track.tid));
+
+        let mut colours = HashMap::new();
+        let mut entries = Vec::new();
+        match coloring {
+            Coloring::Particle => {
+                let pids: BTreeSet<i32> = tracks.iter().map(|(_, track)| track.pid).collect();
+                for pid in pids {
+                    let colour = particle_colour(pid);
+                    entries.push((Particle::name(pid).into_owned(), colour));
+                }
+                for (index, track) in tracks.iter() {
+                    colours.insert((*index, track.tid), particle_colour(track.pid));
+                }
+            },
+            Coloring::Energy | Coloring::Time => {
+                let value = |track: &data::event::Track| {
+                    let vertex = track.vertices.first()?;
+                    match coloring {
+                        Coloring::Energy => (vertex.energy > 0.0).then(|| vertex.energy.log10()),
+                        _ => Some(vertex.time),
+                    }
+                };
+                let values: Vec<_> = tracks
+                    .iter()
+                    .filter_map(|(index, track)| {
+                        value(track).map(|value| ((*index, track.tid), value))
+                    })
+                    .collect();
+                let min = values.iter().map(|(_, v)| *v).fold(f32::INFINITY, f32::min);
+                let max = values.iter().map(|(_, v)| *v).fold(f32::NEG_INFINITY, f32::max);
+                let scale = |v: f32| if max > min { (v - min) / (max - min) } else { 0.5 };
+                for (key, value) in values.iter() {
+                    colours.insert(*key, gradient(scale(*value)));
+                }
+                if !values.is_empty() {
+                    let n = GRADIENT.len();
+                    for i in 0..n {
+                        let t = i as f32 / (n - 1) as f32;
+                        let value = min + t * (max - min);
+                        let label = match coloring {
+                            Coloring::Energy => crate::ui::uformat(10_f32.powf(value)),
+                            _ => tformat(value),
+                        };
+                        entries.push((label, gradient(t)));
+                    }
+                }
+            },
+            Coloring::Generation => {
+                let by_index: HashMap<usize, &EventData> = events.iter().copied().collect();
+                let mut depths: HashMap<(usize, i32), usize> = HashMap::new();
+                for (index, track) in tracks.iter() {
+                    let event = by_index[index];
+                    let mut depth = 0;
+                    let mut parent = track.parent;
+                    while let Some(mother) = event.tracks.get(&parent) {
+                        depth += 1;
+                        parent = mother.parent;
+                        if depth > event.tracks.len() {
+                            break // Guard against cyclic data.
+                        }
+                    }
+                    depths.insert((*index, track.tid), depth);
+                }
+                let generations: BTreeSet<usize> = depths.values().copied().collect();
+                for generation in generations {
+                    let colour = categorical(generation);
+                    entries.push((format!("{}", generation), colour));
+                }
+                for (key, depth) in depths {
+                    colours.insert(key, categorical(depth));
+                }
+            },
+            Coloring::Creator => {
+                let creators: BTreeSet<&str> = tracks
+                    .iter()
+                    .map(|(_, track)| track.creator.as_str())
+                    .collect();
+                let creators: HashMap<&str, LinearRgba> = creators
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, creator)| {
+                        let colour = categorical(i);
+                        entries.push((creator.to_owned(), colour));
+                        (creator, colour)
+                    })
+                    .collect();
+                for (index, track) in tracks.iter() {
+                    colours.insert((*index, track.tid), creators[track.creator.as_str()]);
+                }
+            },
+            Coloring::Charge => {
+                let charges: BTreeSet<Option<i32>> = tracks
+                    .iter()
+                    .map(|(_, track)| charge(track.pid))
+                    .collect();
+                for charge in charges {
+                    let label = match charge {
+                        Some(-1) => "negative",
+                        Some(0) => "neutral",
+                        Some(_) => "positive",
+                        None => "unknown",
+                    };
+                    entries.push((label.to_owned(), charge_colour(charge)));
+                }
+                for (index, track) in tracks.iter() {
+                    let charge = charge(track.pid);
+                    colours.insert((*index, track.tid), charge_colour(charge));
+                }
+            },
+            Coloring::Event => {
+                for (i, (index, _)) in events.iter().enumerate() {
+                    if i < PALETTE.len() {
+                        entries.push((format!("event {}", index), categorical(i)));
+                    } else {
+                        entries.push(("...".to_owned(), css::GRAY.into()));
+                        break
+                    }
+                }
+                let tints: HashMap<usize, LinearRgba> = events
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (index, _))| (*index, categorical(i)))
+                    .collect();
+                for (index, track) in tracks.iter() {
+                    colours.insert((*index, track.tid), tints[index]);
+                }
+            },
+        }
+
+        let title = format!("Colors [{}]", coloring.name());
+        Self { tracks: colours, legend: Legend { title, entries } }
+    }
+
+    pub fn get(&self, event: usize, tid: i32) -> LinearRgba {
+        self.tracks
+            .get(&(event, tid))
+            .copied()
+            .unwrap_or(LinearRgba::WHITE)
+    }
+}
+
+fn particle_colour(pid: i32) -> LinearRgba {
+    match COLOURS.get(&pid) {
+        Some(colour) => *colour,
+        None => categorical(pid.unsigned_abs() as usize),
+    }
+}
+
+fn categorical(index: usize) -> LinearRgba {
+    PALETTE[index % PALETTE.len()].into()
+}
+
+pub(super) fn gradient(t: f32) -> LinearRgba {
+    let t = t.clamp(0.0, 1.0) * (GRADIENT.len() - 1) as f32;
+    let i = (t.floor() as usize).min(GRADIENT.len() - 2);
+    let a = LinearRgba::from(GRADIENT[i]);
+    let b = LinearRgba::from(GRADIENT[i + 1]);
+    a.mix(&b, t - i as f32)
+}
+
+fn charge_colour(charge: Option<i32>) -> LinearRgba {
+    match charge {
+        Some(-1) => css::DODGER_BLUE.into(),
+        Some(0) => css::GOLD.into(),
+        Some(_) => css::CRIMSON.into(),
+        None => css::GRAY.into(),
+    }
+}
+
+/// Sign of the electric charge, if the particle is known.
+fn charge(pid: i32) -> Option<i32> {
+    Particle::from_pid(pid).map(|particle| {
+        if particle.charge > 0.0 { 1 } else if particle.charge < 0.0 { -1 } else { 0 }
+    })
+}
+
+fn tformat(time: f32) -> String {
+    if time.abs() < 1E+03 {
+        format!("{:.3} ns", time)
+    } else if time.abs() < 1E+06 {
+        format!("{:.3} us", time * 1E-03)
+    } else if time.abs() < 1E+09 {
+        format!("{:.3} ms", time * 1E-06)
+    } else {
+        format!("{:.3} s", time * 1E-09)
+    }
+}
@@ -1,9 +1,13 @@
 use bevy::prelude::*;
 use crate::world_to_view;
 use crate::drone::Drone;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::ops::Index;
 use std::sync::Mutex;
 
-pub(crate) use data::event::{Events, Event, Track, Vertex};
+pub(crate) use data::event::{Event, Overlay, Track, Vertex, Voxels};
+use data::event::Columns;
 
 // ===============================================================================================
 //
@@ -17,10 +21,127 @@ pub(crate) fn take() -> Option<Events> {
     EVENTS.lock().unwrap().take()
 }
 
-pub fn set(events: Events) {
+pub fn set(events: data::event::Events) {
+    *EVENTS.lock().unwrap() = Some(Events::Decoded(events));
+}
+
+/// Set columnar events, which are reconstructed on demand.
+pub fn set_columns(columns: Columns) {
+    let events = Events::Columns { columns, cache: HashMap::new() };
     *EVENTS.lock().unwrap() = Some(events);
 }
 
+pub(crate) enum Events {
+    Decoded(data::event::Events),
+    Columns { columns: Columns, cache: HashMap<usize, Event> },
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::Decoded(data::event::Events::default())
+    }
+}
+
+impl Events {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Decoded(events) => events.0.len(),
+            Self::Columns { columns, .. } => columns.len(),
+        }
+    }
+
+    /// Sorted event ids.
+    pub fn keys(&self) -> Vec<usize> {
+        match self {
+            Self::Decoded(events) => {
+                let mut keys: Vec<_> = events.0.keys().copied().collect();
+                keys.sort();
+                keys
+            },
+            Self::Columns { columns, .. } => columns.events
+                .iter()
+                .map(|row| row.event as usize)
+                .collect(),
+        }
+    }
+
+    /// Get an event, provided that it has been loaded.
+    pub fn get(&self, index: &usize) -> Option<&Event> {
+        match self {
+            Self::Decoded(events) => events.0.get(index),
+            Self::Columns { cache, .. } => cache.get(index),
+        }
+    }
+
+    /// Reconstruct an event from columns, if not already done.
+    pub fn load(&mut self, index: usize) {
+        let Self::Columns { columns, cache } = self else { return };
+        if let Entry::Vacant(entry) = cache.entry(index) {
+            let Some(event) = columns.get(index) else { return };
+            entry.insert(event);
+        }
+    }
+
+    /// Drop reconstructed events which are no longer displayed.
+    pub fn evict(&mut self, indices: &[usize]) {
+        let Self::Columns { cache, .. } = self else { return };
+        cache.retain(|index, _| indices.contains(index));
+    }
+}
+
+impl Events {
+    /// Visit the vertices of all events, without reconstructing them.
+    pub fn for_each_vertex<F: FnMut(VertexSample)>(&self, mut f: F) {
+        fn visit<F, I>(pid: i32, vertices: I, f: &mut F)
+        where
+            F: FnMut(VertexSample),
+            I: Iterator<Item=(Vec3, f32)>,
+        {
+            let mut previous: Option<f32> = None;
+            for (position, energy) in vertices {
+                let loss = previous.map(|previous| previous - energy).unwrap_or(0.0);
+                previous = Some(energy);
+                f(VertexSample { pid, position, loss });
+            }
+        }
+
+        match self {
+            Self::Decoded(events) => for event in events.0.values() {
+                for track in event.tracks.values() {
+                    let vertices = track.vertices
+                        .iter()
+                        .map(|vertex| (vertex.position.to_view(), vertex.energy));
+                    visit(track.pid, vertices, &mut f);
+                }
+            },
+            Self::Columns { columns, .. } => for track in columns.tracks.iter() {
+                let start = track.start as usize;
+                let end = start + track.size as usize;
+                let vertices = columns.vertices[start..end]
+                    .iter()
+                    .map(|vertex| (vertex.position.to_view(), vertex.energy));
+                visit(track.pid, vertices, &mut f);
+            },
+        }
+    }
+}
+
+/// A vertex of a track, with the kinetic energy lost since the previous vertex.
+pub(crate) struct VertexSample {
+    pub pid: i32,
+    pub position: Vec3,
+    pub loss: f32,
+}
+
+impl Index<&usize> for Events {
+    type Output = Event;
+
+    fn index(&self, index: &usize) -> &Event {
+        self.get(index)
+            .expect("event not loaded")
+    }
+}
+
 pub(crate) trait Target {
     fn target(&self) -> Transform;
 }
@@ -59,3 +180,10 @@ impl ToView for data::event::Vec3 {
         world_to_view().transform_point3(p)
     }
 }
+
+impl ToView for [f32; 3] {
+    #[inline]
+    fn to_view(&self) -> Vec3 {
+        world_to_view().transform_point3(Vec3::from_array(*self))
+    }
+}
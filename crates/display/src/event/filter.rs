@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use std::sync::Mutex;
+
+
+// ===============================================================================================
+//
+// Events filter.
+//
+// ===============================================================================================
+
+static FILTER: Mutex<Option<data::event::Filter>> = Mutex::new(None);
+
+/// Set the events filter, e.g. hiding low energy tracks.
+pub fn set_filter(filter: data::event::Filter) {
+    *FILTER.lock().unwrap() = Some(filter);
+}
+
+/// The current events filter. Filtered tracks and vertices are not drawn, thus not pickable.
+#[derive(Default, Deref, DerefMut, Resource)]
+pub(crate) struct Filter(pub data::event::Filter);
+
+pub(crate) fn update_filter(mut filter: ResMut<Filter>) {
+    if let Some(new_filter) = FILTER.lock().unwrap().take() {
+        filter.0 = new_filter;
+    }
+}
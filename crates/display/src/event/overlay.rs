@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use super::{Coloring, Events};
+
+
+// ===============================================================================================
+//
+// Overlaid events.
+//
+// ===============================================================================================
+
+static OVERLAY: Mutex<Option<Option<data::event::Overlay>>> = Mutex::new(None);
+
+/// Overlay a range of events, or display a single event if `None`.
+pub fn set_overlay(overlay: Option<data::event::Overlay>) {
+    *OVERLAY.lock().unwrap() = Some(overlay);
+}
+
+/// Maximum number of overlaid events, besides the current one.
+pub(crate) const MAX_OVERLAID: usize = 100;
+
+/// The current overlay, if any.
+#[derive(Default, Deref, DerefMut, Resource)]
+pub(crate) struct Overlay(pub Option<data::event::Overlay>);
+
+impl Overlay {
+    /// Ids of the displayed events, starting with the current one. At most `MAX_OVERLAID` events
+    /// are overlaid, following the current one and wrapping around.
+    pub fn indices(&self, events: &Events) -> Vec<usize> {
+        let mut indices = vec![events.index];
+        if let Some(overlay) = self.0.as_ref() {
+            let start = events.keys.partition_point(|key| *key <= events.index);
+            let (before, after) = events.keys.split_at(start);
+            indices.extend(after
+                .iter()
+                .chain(before.iter())
+                .filter(|key| (**key != events.index) && overlay.contains(**key))
+                .take(MAX_OVERLAID)
+            );
+        }
+        indices
+    }
+
+    pub fn density(&self) -> bool {
+        self.0.as_ref().is_some_and(|overlay| overlay.density)
+    }
+}
+
+/// Apply any pending overlay, switching to per-event colours when overlaying.
+pub(crate) fn update_overlay(mut overlay: ResMut<Overlay>, mut coloring: ResMut<Coloring>) {
+    if let Some(new_overlay) = OVERLAY.lock().unwrap().take() {
+        if overlay.is_none() && new_overlay.is_some() {
+            *coloring = Coloring::Event;
+        } else if overlay.is_some() && new_overlay.is_none() && (*coloring == Coloring::Event) {
+            *coloring = Coloring::default();
+        }
+        overlay.0 = new_overlay;
+    }
+}
+
+/// Relative density of vertices, in [0, 1], using a logarithmic scale.
+pub(crate) fn density(positions: &[Vec3]) -> Vec<f32> {
+    const BINS: f32 = 100.0;
+
+    if positions.is_empty() {
+        return Vec::new()
+    }
+    let (min, max) = positions.iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), position| (min.min(*position), max.max(*position)),
+    );
+    let size = ((max - min).max_element() / BINS).max(f32::EPSILON);
+    let cell = |position: &Vec3| ((*position - min) / size).floor().as_ivec3();
+
+    let mut counts: HashMap<IVec3, usize> = HashMap::new();
+    for position in positions.iter() {
+        *counts.entry(cell(position)).or_insert(0) += 1;
+    }
+    let max_count = counts.values().copied().max().unwrap_or(1);
+    let scale = if max_count > 1 { 1.0 / (max_count as f32).ln() } else { 0.0 };
+    positions
+        .iter()
+        .map(|position| (counts[&cell(position)] as f32).ln() * scale)
+        .collect()
+}
@@ -0,0 +1,199 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use data::elements::ELEMENTS;
+
+
+// ===============================================================================================
+//
+// PDG particles table.
+//
+// ===============================================================================================
+
+/// Properties of a particle, as determined by its PDG code.
+#[derive(Clone)]
+pub(crate) struct Particle {
+    pub name: Cow<'static, str>,
+    /// Electric charge, in units of e.
+    pub charge: f32,
+    /// Rest mass, in MeV.
+    pub mass: f32,
+}
+
+impl Particle {
+    pub fn from_pid(pid: i32) -> Option<Self> {
+        if let Some(row) = TABLE.get(&pid.unsigned_abs()) {
+            let name = if pid > 0 {
+                row.name
+            } else {
+                row.anti?
+            };
+            let charge = (pid.signum() * row.charge) as f32 / 3.0;
+            Some(Self { name: Cow::Borrowed(name), charge, mass: row.mass as f32 })
+        } else {
+            Self::from_nucleus(pid)
+        }
+    }
+
+    /// Particle name, or its bracketed PDG code if unknown.
+    pub fn name(pid: i32) -> Cow<'static, str> {
+        match Self::from_pid(pid) {
+            Some(particle) => particle.name,
+            None => Cow::Owned(format!("[{}]", pid)),
+        }
+    }
+
+    /// Electric charge, formatted in units of e.
+    pub fn charge_label(&self) -> String {
+        let thirds = (self.charge * 3.0).round() as i32;
+        if thirds == 0 {
+            "0".to_string()
+        } else if thirds % 3 == 0 {
+            format!("{:+} e", thirds / 3)
+        } else {
+            format!("{:+}/3 e", thirds)
+        }
+    }
+
+    /// Total energy, in MeV, given the kinetic one.
+    pub fn total_energy(&self, kinetic: f32) -> f32 {
+        kinetic + self.mass
+    }
+
+    /// Nuclei, with PDG codes of the form 10LZZZAAAI.
+    fn from_nucleus(pid: i32) -> Option<Self> {
+        let code = pid.unsigned_abs();
+        if code < 1_000_000_000 {
+            return None
+        }
+        let isomer = code % 10;
+        let a = ((code / 10) % 1000) as i32;
+        let z = ((code / 10_000) % 1000) as i32;
+        let lambdas = (code / 10_000_000) % 10;
+        if (a == 0) || (z > a) || (lambdas > 0) {
+            return None
+        }
+        let symbol = ELEMENTS.get((z as usize).wrapping_sub(1))?;
+        let name = match (z, a, isomer) {
+            (1, 1, 0) => Cow::Borrowed("proton"),
+            (1, 2, 0) => Cow::Borrowed("deuteron"),
+            (1, 3, 0) => Cow::Borrowed("triton"),
+            (2, 4, 0) => Cow::Borrowed("alpha"),
+            (_, _, 0) => Cow::Owned(format!("{}{}", symbol, a)),
+            _ => Cow::Owned(format!("{}{}*", symbol, a)),
+        };
+        let name = if pid > 0 {
+            name
+        } else {
+            Cow::Owned(format!("anti_{}", name))
+        };
+        let charge = (pid.signum() * z) as f32;
+        let mass = nuclear_mass(z, a) as f32;
+        Some(Self { name, charge, mass })
+    }
+}
+
+struct Row {
+    name: &'static str,
+    /// Antiparticle name, or `None` for self-conjugate particles.
+    anti: Option<&'static str>,
+    /// Electric charge, in units of e/3.
+    charge: i32,
+    mass: f64,
+}
+
+const fn row(
+    name: &'static str,
+    anti: Option<&'static str>,
+    charge: i32,
+    mass: f64,
+) -> Row {
+    Row { name, anti, charge, mass }
+}
+
+static TABLE: LazyLock<HashMap<u32, Row>> = LazyLock::new(|| HashMap::from([
+    // Quarks.
+    (1, row("d", Some("anti_d"), -1, 4.67)),
+    (2, row("u", Some("anti_u"), 2, 2.16)),
+    (3, row("s", Some("anti_s"), -1, 93.4)),
+    (4, row("c", Some("anti_c"), 2, 1.2730E+03)),
+    (5, row("b", Some("anti_b"), -1, 4.183E+03)),
+    (6, row("t", Some("anti_t"), 2, 1.7257E+05)),
+    // Leptons.
+    (11, row("e-", Some("e+"), -3, 0.51099895)),
+    (12, row("nu_e", Some("anti_nu_e"), 0, 0.0)),
+    (13, row("mu-", Some("mu+"), -3, 105.6583755)),
+    (14, row("nu_mu", Some("anti_nu_mu"), 0, 0.0)),
+    (15, row("tau-", Some("tau+"), -3, 1776.93)),
+    (16, row("nu_tau", Some("anti_nu_tau"), 0, 0.0)),
+    // Gauge and Higgs bosons.
+    (21, row("gluon", None, 0, 0.0)),
+    (22, row("gamma", None, 0, 0.0)),
+    (23, row("Z0", None, 0, 9.11880E+04)),
+    (24, row("W+", Some("W-"), 3, 8.03692E+04)),
+    (25, row("H", None, 0, 1.2520E+05)),
+    // Light mesons.
+    (111, row("pi0", None, 0, 134.9768)),
+    (211, row("pi+", Some("pi-"), 3, 139.57039)),
+    (113, row("rho0", None, 0, 775.26)),
+    (213, row("rho+", Some("rho-"), 3, 775.11)),
+    (221, row("eta", None, 0, 547.862)),
+    (223, row("omega", None, 0, 782.66)),
+    (331, row("eta_prime", None, 0, 957.78)),
+    (333, row("phi", None, 0, 1019.461)),
+    // Strange mesons.
+    (130, row("kaon0L", None, 0, 497.611)),
+    (310, row("kaon0S", None, 0, 497.611)),
+    (311, row("kaon0", Some("anti_kaon0"), 0, 497.611)),
+    (321, row("kaon+", Some("kaon-"), 3, 493.677)),
+    // Charmed and bottom mesons.
+    (411, row("D+", Some("D-"), 3, 1869.66)),
+    (421, row("D0", Some("anti_D0"), 0, 1864.84)),
+    (431, row("Ds+", Some("Ds-"), 3, 1968.35)),
+    (443, row("J/psi", None, 0, 3096.900)),
+    (511, row("B0", Some("anti_B0"), 0, 5279.66)),
+    (521, row("B+", Some("B-"), 3, 5279.34)),
+    (531, row("Bs0", Some("anti_Bs0"), 0, 5366.92)),
+    // Baryons.
+    (2212, row("proton", Some("anti_proton"), 3, 938.27208816)),
+    (2112, row("neutron", Some("anti_neutron"), 0, 939.56542052)),
+    (3122, row("lambda", Some("anti_lambda"), 0, 1115.683)),
+    (3222, row("sigma+", Some("anti_sigma+"), 3, 1189.37)),
+    (3212, row("sigma0", Some("anti_sigma0"), 0, 1192.642)),
+    (3112, row("sigma-", Some("anti_sigma-"), -3, 1197.449)),
+    (3322, row("xi0", Some("anti_xi0"), 0, 1314.86)),
+    (3312, row("xi-", Some("anti_xi-"), -3, 1321.71)),
+    (3334, row("omega-", Some("anti_omega-"), -3, 1672.45)),
+    (4122, row("lambda_c+", Some("anti_lambda_c+"), 3, 2286.46)),
+]));
+
+/// Nuclear mass (in MeV), using tabulated values for light nuclei and the semi-empirical mass
+/// formula otherwise.
+fn nuclear_mass(z: i32, a: i32) -> f64 {
+    const PROTON_MASS: f64 = 938.27208816;
+    const NEUTRON_MASS: f64 = 939.56542052;
+
+    match (z, a) {
+        (1, 1) => return PROTON_MASS,
+        (1, 2) => return 1875.61294,
+        (1, 3) => return 2808.92113,
+        (2, 3) => return 2808.39161,
+        (2, 4) => return 3727.37941,
+        _ => (),
+    }
+
+    let n = (a - z) as f64;
+    let z = z as f64;
+    let a = a as f64;
+    let pairing = match ((z as i32) % 2, (n as i32) % 2) {
+        (0, 0) => 1.0,
+        (1, 1) => -1.0,
+        _ => 0.0,
+    };
+    let binding = 15.75 * a
+        - 17.8 * a.powf(2.0 / 3.0)
+        - 0.711 * z * (z - 1.0) / a.cbrt()
+        - 23.7 * (a - 2.0 * z).powi(2) / a
+        + pairing * 11.18 / a.sqrt();
+    z * PROTON_MASS + n * NEUTRON_MASS - binding.max(0.0)
+}
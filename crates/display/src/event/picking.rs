@@ -1,37 +1,120 @@
 use bevy::prelude::*;
-use bevy::math::Vec3A;
+use bevy::math::{Affine3A, Vec3A};
 use bevy::math::bounding::{BoundingSphere, RayCast3d};
 use bevy::window::PrimaryWindow;
+use bevy_polyline::prelude::*;
 use crate::app::AppState;
 use crate::ui::{UiEvent, UiRoot};
-use super::{EventCamera, Track, Vertex, VertexSize};
+use std::collections::HashMap;
+use super::{EventCamera, Events, Track, TrackPiece, Vertex, VertexSize};
+
+mod bvh;
+
+use bvh::{Bounds, Bvh};
 
 
 pub struct PickingPlugin;
 
 impl Plugin for PickingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, cursor_selection.run_if(in_state(AppState::Display)));
+        app
+            .init_resource::<PickingIndex>()
+            .add_systems(Update, (
+                update_index,
+                cursor_selection.after(update_index),
+            ).run_if(in_state(AppState::Display)));
+    }
+}
+
+/// Picking tolerance around tracks segments, in pixels.
+const TOLERANCE: f32 = 4.0;
+
+/// A picked track, with the matching vertices and the closest point to the cursor ray, if the
+/// track was picked by a segment.
+pub(crate) struct Pick<'a> {
+    pub track: &'a Track,
+    pub vertices: Vec<Vertex>,
+    pub point: Option<Vec3>,
+}
+
+/// Pickable primitives of the displayed events.
+#[derive(Default, Resource)]
+struct PickingIndex(Option<Bvh<Primitive>>);
+
+enum Primitive {
+    Vertex { entity: Entity, center: Vec3, radius: f32 },
+    Segment { track: Entity, index: usize, a: Vec3, b: Vec3 },
+}
+
+/// Rebuild the picking index when tracks are spawned or removed.
+fn update_index(
+    added: Query<(), Added<Track>>,
+    mut removed: RemovedComponents<Track>,
+    tracks: Query<(Entity, &PolylineHandle), With<Track>>,
+    pieces: Query<(&TrackPiece, &ChildOf, &PolylineHandle)>,
+    vertices: Query<(Entity, &VertexSize, &Transform), With<Vertex>>,
+    polylines: Res<Assets<Polyline>>,
+    mut index: ResMut<PickingIndex>,
+) {
+    let removed = removed.read().count() > 0;
+    if added.is_empty() && !removed {
+        return
+    }
+
+    let mut primitives = Vec::new();
+    for (entity, size, transform) in vertices.iter() {
+        let center = transform.translation;
+        let bounds = Bounds::from_sphere(center, size.0);
+        primitives.push((bounds, Primitive::Vertex { entity, center, radius: size.0 }));
+    }
+    for (track, polyline) in tracks.iter() {
+        let Some(polyline) = polylines.get(&polyline.0) else { continue };
+        for (index, segment) in polyline.vertices.windows(2).enumerate() {
+            let (a, b) = (segment[0], segment[1]);
+            let bounds = Bounds::from_points(a, b);
+            primitives.push((bounds, Primitive::Segment { track, index, a, b }));
+        }
+    }
+    for (piece, childof, polyline) in pieces.iter() {
+        let Some(polyline) = polylines.get(&polyline.0) else { continue };
+        for (index, segment) in polyline.vertices.windows(2).enumerate() {
+            let (a, b) = (segment[0], segment[1]);
+            let bounds = Bounds::from_points(a, b);
+            let (track, index) = (childof.parent(), piece.first + index);
+            primitives.push((bounds, Primitive::Segment { track, index, a, b }));
+        }
     }
+    index.0 = Some(Bvh::new(primitives));
 }
 
+/// Pick tracks and vertices under the cursor, when the cursor, the camera or the displayed
+/// events change.
 fn cursor_selection(
     window: Query<&mut Window, With<PrimaryWindow>>,
     uis: Query<(&ComputedNode, &GlobalTransform), With<UiRoot>>,
-    camera: Query<(&Camera, &GlobalTransform), With<EventCamera>>,
+    camera: Query<(&Camera, &GlobalTransform, &Projection), With<EventCamera>>,
     tracks: Query<&Track>,
-    vertices: Query<(&Vertex, &VertexSize, &Transform, &ChildOf)>,
+    vertices: Query<(&Vertex, &ChildOf)>,
     ui_event: Query<Entity, With<UiEvent>>,
+    events: Res<Events>,
+    index: Res<PickingIndex>,
+    mut last_view: Local<Option<(Vec2, Affine3A)>>,
     mut commands: Commands,
 ) {
-    if !ui_event.is_empty() {
-        commands.entity(ui_event.single().unwrap()).despawn();
-    }
-    if window.is_empty() || camera.is_empty() || tracks.is_empty() || vertices.is_empty() {
+    let Ok(window) = window.single() else { return };
+    let Ok((camera, camera_transform, projection)) = camera.single() else { return };
+    let cursor = window.cursor_position();
+    let view = cursor.map(|cursor| (cursor, camera_transform.affine()));
+    if (view == *last_view) && !index.is_changed() {
         return
     }
+    *last_view = view;
 
-    let Some(cursor) = window.single().unwrap().cursor_position() else { return };
+    for entity in ui_event.iter() {
+        commands.entity(entity).despawn();
+    }
+    let Some(cursor) = cursor else { return };
+    let Some(bvh) = index.0.as_ref() else { return };
     for (node, transform) in uis.iter() {
         let rect = Rect::from_center_size(
             transform.translation().xy(),
@@ -42,24 +125,130 @@ fn cursor_selection(
         }
     }
 
-    let (camera, camera_transform) = camera.single().unwrap();
     let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else { return };
 
-    let mut matches = Vec::new();
-    for (vertex, size, transform, childof) in vertices.iter() {
-        let bounding_sphere = BoundingSphere {
-            center: Vec3A::from(transform.translation),
-            sphere: Sphere { radius: size.0 },
-        };
-        let raycast = RayCast3d::from_ray(ray, f32::MAX);
-        if let Some(_) = raycast.sphere_intersection_at(&bounding_sphere) {
-            let track = tracks.get(childof.parent()).unwrap();
-            matches.push((track, vertex))
+    // Angular size of the tolerance, used for culling candidates.
+    let height = camera.logical_viewport_size().map(|size| size.y).unwrap_or(window.height());
+    let slope = match projection {
+        Projection::Perspective(perspective) => {
+            2.0 * (0.5 * perspective.fov).tan() * TOLERANCE / height.max(1.0)
+        },
+        _ => 0.0,
+    };
+
+    // Segments are clipped slightly in front of the near plane, such that they can be projected.
+    let near = match projection {
+        Projection::Perspective(perspective) => perspective.near,
+        Projection::Orthographic(orthographic) => orthographic.near,
+        _ => 0.0,
+    };
+    let near = 1.001 * near;
+    let origin = camera_transform.translation();
+    let forward = camera_transform.forward().as_vec3();
+
+    let mut picks: HashMap<Entity, Pick> = HashMap::new();
+    let mut segments: HashMap<Entity, (f32, usize, Vec3, Vec3)> = HashMap::new();
+    let raycast = RayCast3d::from_ray(ray, f32::MAX);
+    bvh.visit_cone(&ray, slope, |primitive| match primitive {
+        Primitive::Vertex { entity, center, radius } => {
+            // Pick vertices, by their bounding sphere.
+            let bounding_sphere = BoundingSphere {
+                center: Vec3A::from(*center),
+                sphere: Sphere { radius: *radius },
+            };
+            if raycast.sphere_intersection_at(&bounding_sphere).is_none() {
+                return
+            }
+            let Ok((vertex, childof)) = vertices.get(*entity) else { return };
+            let Ok(track) = tracks.get(childof.parent()) else { return };
+            picks
+                .entry(childof.parent())
+                .or_insert_with(|| Pick { track, vertices: Vec::new(), point: None })
+                .vertices
+                .push(vertex.clone());
+        },
+        Primitive::Segment { track, index, a, b } => {
+            // Pick tracks segments, by their screen distance to the cursor.
+            let Some((ca, cb)) = clip_near(*a, *b, origin, forward, near) else { return };
+            let Ok(sa) = camera.world_to_viewport(camera_transform, ca) else { return };
+            let Ok(sb) = camera.world_to_viewport(camera_transform, cb) else { return };
+            let distance = segment_distance(cursor, sa, sb);
+            if distance > TOLERANCE {
+                return
+            }
+            let closest = segments.get(track).is_none_or(|(d, ..)| distance < *d);
+            if closest {
+                segments.insert(*track, (distance, *index, *a, *b));
+            }
+        },
+    });
+
+    for (entity, (_, index, a, b)) in segments.into_iter() {
+        let Ok(track) = tracks.get(entity) else { continue };
+        let point = closest_point(ray, a, b);
+        let pick = picks
+            .entry(entity)
+            .or_insert_with(|| Pick { track, vertices: Vec::new(), point: None });
+        pick.point = Some(point);
+        if pick.vertices.is_empty() {
+            let segment = events.data
+                .get(&track.event)
+                .and_then(|event| event.tracks.get(&track.tid))
+                .and_then(|data| data.vertices.get(index..(index + 2)));
+            if let Some(segment) = segment {
+                pick.vertices.extend(segment.iter().map(Vertex::from));
+            }
         }
     }
-    if matches.is_empty() {
+
+    let picks: Vec<_> = picks
+        .into_values()
+        .filter(|pick| !pick.vertices.is_empty())
+        .collect();
+    if picks.is_empty() {
         return
     }
 
-    UiEvent::spawn_info(&mut commands, cursor, matches);
+    UiEvent::spawn_info(&mut commands, cursor, picks);
+}
+
+/// Part of the segment [a, b] lying beyond a depth of `near` along the camera axis.
+fn clip_near(a: Vec3, b: Vec3, origin: Vec3, forward: Vec3, near: f32) -> Option<(Vec3, Vec3)> {
+    let da = forward.dot(a - origin) - near;
+    let db = forward.dot(b - origin) - near;
+    if (da < 0.0) && (db < 0.0) {
+        return None
+    } else if (da >= 0.0) && (db >= 0.0) {
+        return Some((a, b))
+    }
+    let p = a.lerp(b, da / (da - db));
+    if da < 0.0 { Some((p, b)) } else { Some((a, p)) }
+}
+
+/// Distance from a point to a 2D segment.
+fn segment_distance(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let u = b - a;
+    let length2 = u.length_squared();
+    let t = if length2 > 0.0 {
+        ((point - a).dot(u) / length2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    point.distance(a + t * u)
+}
+
+/// Point of the segment [a, b] closest to the ray.
+fn closest_point(ray: Ray3d, a: Vec3, b: Vec3) -> Vec3 {
+    let u = b - a;
+    let v = *ray.direction;
+    let w = a - ray.origin;
+    let (uu, uv, vv) = (u.dot(u), u.dot(v), v.dot(v));
+    let (uw, vw) = (u.dot(w), v.dot(w));
+    let denominator = uu * vv - uv * uv;
+    let s = if denominator > f32::EPSILON {
+        ((uv * vw - vv * uw) / denominator).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    a + s * u
 }
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+
+// ===============================================================================================
+//
+// Bounding volumes hierarchy.
+//
+// ===============================================================================================
+
+/// Axis aligned bounding box.
+#[derive(Clone, Copy)]
+pub(crate) struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Bounds {
+    pub fn from_points(a: Vec3, b: Vec3) -> Self {
+        Self { min: a.min(b), max: a.max(b) }
+    }
+
+    pub fn from_sphere(center: Vec3, radius: f32) -> Self {
+        Self { min: center - radius, max: center + radius }
+    }
+
+    fn center(&self) -> Vec3 {
+        0.5 * (self.min + self.max)
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        Self { min: self.min.min(other.min), max: self.max.max(other.max) }
+    }
+
+    /// Check if the ray intersects these bounds, inflated by `slope` times the distance to the
+    /// ray origin (i.e. by a cone around the ray).
+    fn intersects_cone(&self, ray: &Ray3d, slope: f32) -> bool {
+        let center = self.center();
+        let half_width = 0.5 * (self.max - self.min);
+        let far = (center - ray.origin).length() + half_width.length();
+        let inflation = Vec3::splat(slope * far);
+        let min = self.min - inflation;
+        let max = self.max + inflation;
+
+        // Slabs test.
+        let direction = *ray.direction;
+        let mut t_min = 0.0_f32;
+        let mut t_max = f32::INFINITY;
+        for i in 0..3 {
+            let (o, d) = (ray.origin[i], direction[i]);
+            if d.abs() < f32::EPSILON {
+                if (o < min[i]) || (o > max[i]) {
+                    return false
+                }
+            } else {
+                let t0 = (min[i] - o) / d;
+                let t1 = (max[i] - o) / d;
+                t_min = t_min.max(t0.min(t1));
+                t_max = t_max.min(t0.max(t1));
+                if t_min > t_max {
+                    return false
+                }
+            }
+        }
+        true
+    }
+}
+
+enum Node {
+    Branch { bounds: Bounds, left: usize, right: usize },
+    Leaf { bounds: Bounds, start: usize, end: usize },
+}
+
+impl Node {
+    fn bounds(&self) -> &Bounds {
+        match self {
+            Self::Branch { bounds, .. } => bounds,
+            Self::Leaf { bounds, .. } => bounds,
+        }
+    }
+}
+
+pub(crate) struct Bvh<T> {
+    nodes: Vec<Node>,
+    items: Vec<(Bounds, T)>,
+}
+
+impl<T> Bvh<T> {
+    const LEAF_SIZE: usize = 8;
+
+    pub fn new(mut items: Vec<(Bounds, T)>) -> Self {
+        let mut nodes = Vec::new();
+        if !items.is_empty() {
+            let n = items.len();
+            Self::build(&mut nodes, &mut items, 0, n);
+        }
+        Self { nodes, items }
+    }
+
+    /// Recursively split items at the median of their centers, along the widest axis.
+    fn build(nodes: &mut Vec<Node>, items: &mut [(Bounds, T)], start: usize, end: usize) -> usize {
+        let bounds = items[start..end]
+            .iter()
+            .skip(1)
+            .fold(items[start].0, |bounds, (item, _)| bounds.union(item));
+        let index = nodes.len();
+        if end - start <= Self::LEAF_SIZE {
+            nodes.push(Node::Leaf { bounds, start, end });
+            return index
+        }
+
+        let width = bounds.max - bounds.min;
+        let axis = if (width.x >= width.y) && (width.x >= width.z) {
+            0
+        } else if width.y >= width.z {
+            1
+        } else {
+            2
+        };
+        let middle = (end - start) / 2;
+        items[start..end].select_nth_unstable_by(middle, |a, b| {
+            a.0.center()[axis].total_cmp(&b.0.center()[axis])
+        });
+
+        nodes.push(Node::Leaf { bounds, start, end }); // Placeholder.
+        let left = Self::build(nodes, items, start, start + middle);
+        let right = Self::build(nodes, items, start + middle, end);
+        nodes[index] = Node::Branch { bounds, left, right };
+        index
+    }
+
+    /// Visit the items that might lie within a cone around the ray.
+    pub fn visit_cone<F: FnMut(&T)>(&self, ray: &Ray3d, slope: f32, mut f: F) {
+        if self.nodes.is_empty() {
+            return
+        }
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.bounds().intersects_cone(ray, slope) {
+                continue
+            }
+            match node {
+                Node::Branch { left, right, .. } => {
+                    stack.push(*left);
+                    stack.push(*right);
+                },
+                Node::Leaf { start, end, .. } => {
+                    for (bounds, item) in self.items[*start..*end].iter() {
+                        if bounds.intersects_cone(ray, slope) {
+                            f(item);
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
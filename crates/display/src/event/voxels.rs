@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::view::RenderLayers;
+use crate::app::Removable;
+use crate::geometry::{RootVolume, Volume};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use super::{colours, Events, EventsUpdate, Particle};
+
+
+// ===============================================================================================
+//
+// Vertices density grid.
+//
+// ===============================================================================================
+
+pub(crate) const VOXELS_LAYER: usize = 2;
+
+static VOXELS: Mutex<Option<Option<data::event::Voxels>>> = Mutex::new(None);
+
+/// Show the vertices density grid of all events, or hide it if `None`.
+pub fn set_voxels(voxels: Option<data::event::Voxels>) {
+    *VOXELS.lock().unwrap() = Some(voxels);
+}
+
+/// The current density grid settings, if shown.
+#[derive(Default, Deref, DerefMut, Resource)]
+pub(crate) struct Voxels(pub Option<data::event::Voxels>);
+
+#[derive(Component)]
+pub(crate) struct VoxelsLayer;
+
+pub(crate) fn update_voxels(mut voxels: ResMut<Voxels>) {
+    if let Some(new_voxels) = VOXELS.lock().unwrap().take() {
+        voxels.0 = new_voxels;
+    }
+}
+
+/// Rebuild the grid when its settings, the events or the geometry change.
+pub(crate) fn draw_voxels(
+    voxels: Res<Voxels>,
+    events: Res<Events>,
+    mut ev_update: EventReader<EventsUpdate>,
+    root: Query<&Volume, With<RootVolume>>,
+    new_root: Query<(), Added<RootVolume>>,
+    layer: Query<Entity, With<VoxelsLayer>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let updated = ev_update.read().count() > 0;
+    if !voxels.is_changed() && !updated && new_root.is_empty() {
+        return
+    }
+    for entity in layer.iter() {
+        commands.entity(entity).despawn();
+    }
+    let Some(settings) = voxels.0.as_ref() else { return };
+    let Ok(root) = root.single() else { return };
+
+    let grid = Grid::new(settings, root.aabb.min().into(), root.aabb.max().into(), &events);
+    let Some(mesh) = grid.mesh() else { return };
+    let material = StandardMaterial {
+        base_color: Color::WHITE,
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        cull_mode: None,
+        double_sided: true,
+        ..default()
+    };
+    commands.spawn((
+        VoxelsLayer,
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(material)),
+        Transform::default(),
+        RenderLayers::layer(VOXELS_LAYER),
+        Removable,
+    ));
+}
+
+/// A sparse grid of weights, by cell.
+struct Grid {
+    origin: Vec3,
+    size: f32,
+    weights: HashMap<UVec3, f32>,
+}
+
+impl Grid {
+    fn new(settings: &data::event::Voxels, min: Vec3, max: Vec3, events: &Events) -> Self {
+        // Settings are also received from sockets or sessions, thus bins are bounded here.
+        let bins = settings.bins.clamp(1, data::event::Voxels::MAX_BINS);
+        let extent = max - min;
+        let size = (extent.max_element() / bins as f32).max(f32::EPSILON);
+        let shape = (extent / size).ceil().as_uvec3().max(UVec3::ONE);
+        let mut weights = HashMap::new();
+
+        let mut charges = std::collections::HashMap::new();
+        let mut is_charged = |pid: i32| *charges.entry(pid).or_insert_with(|| {
+            Particle::from_pid(pid).is_some_and(|particle| particle.charge != 0.0)
+        });
+        events.data.for_each_vertex(|sample| {
+            let weight = if settings.energy {
+                if (sample.loss <= 0.0) || !is_charged(sample.pid) {
+                    return
+                }
+                sample.loss
+            } else {
+                1.0
+            };
+            let cell = ((sample.position - min) / size).floor();
+            if cell.cmplt(Vec3::ZERO).any() {
+                return
+            }
+            let cell = cell.as_uvec3();
+            if cell.cmpge(shape).any() {
+                return
+            }
+            *weights.entry(cell).or_insert(0.0) += weight;
+        });
+        Self { origin: min, size, weights }
+    }
+
+    /// Merged mesh of translucent voxels, coloured over three decades below the maximum weight.
+    fn mesh(&self) -> Option<Mesh> {
+        const DECADES: f32 = 3.0;
+
+        let max = self.weights.values().copied().fold(0.0, f32::max);
+        if max <= 0.0 {
+            return None
+        }
+        let threshold = max * 10_f32.powf(-DECADES);
+
+        let mut cells: Vec<_> = self.weights
+            .iter()
+            .filter(|(_, weight)| **weight >= threshold)
+            .collect();
+        cells.sort_by_key(|(cell, _)| (cell.z, cell.y, cell.x));
+
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut colors: Vec<[f32; 4]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        for (cell, weight) in cells {
+            let level = 1.0 + (weight / max).log10() / DECADES;
+            let mut color = colours::gradient(level);
+            color.alpha = 0.05 + 0.45 * level;
+            let min = self.origin + cell.as_vec3() * self.size;
+            add_cube(min, self.size, color.to_f32_array(), &mut positions, &mut colors,
+                &mut indices);
+        }
+
+        let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+            .with_inserted_indices(Indices::U32(indices));
+        Some(mesh)
+    }
+}
+
+fn add_cube(
+    min: Vec3,
+    size: f32,
+    color: [f32; 4],
+    positions: &mut Vec<[f32; 3]>,
+    colors: &mut Vec<[f32; 4]>,
+    indices: &mut Vec<u32>,
+) {
+    const FACES: [u32; 36] = [
+        0, 2, 1, 0, 3, 2, // -z
+        4, 5, 6, 4, 6, 7, // +z
+        0, 1, 5, 0, 5, 4, // -y
+        3, 7, 6, 3, 6, 2, // +y
+        0, 4, 7, 0, 7, 3, // -x
+        1, 2, 6, 1, 6, 5, // +x
+    ];
+
+    let offset = positions.len() as u32;
+    for corner in 0..8 {
+        let dx = if matches!(corner, 1 | 2 | 5 | 6) { size } else { 0.0 };
+        let dy = if matches!(corner, 2 | 3 | 6 | 7) { size } else { 0.0 };
+        let dz = if corner >= 4 { size } else { 0.0 };
+        positions.push((min + Vec3::new(dx, dy, dz)).to_array());
+        colors.push(color);
+    }
+    indices.extend(FACES.iter().map(|index| offset + index));
+}
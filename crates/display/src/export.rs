@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+use bevy::pbr::wireframe::WireframeColor;
+use bevy_polyline::prelude::*;
+use crate::app::AppState;
+use crate::event::{Event, Events, Track, TrackPiece};
+use crate::geometry::{RootVolume, Volume};
+use crate::ui::{TextInputSet, TextInputState};
+use std::path::Path;
+use std::sync::Mutex;
+
+mod glb;
+
+
+pub(crate) struct ExportPlugin;
+
+static EXPORT: Mutex<Option<String>> = Mutex::new(None);
+
+/// Export the displayed scene (volumes and current event) to a glTF binary file (.glb), or the
+/// current event to a Phoenix JSON file (.json).
+pub fn set_export(path: String) {
+    *EXPORT.lock().unwrap() = Some(path);
+}
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, on_keyboard
+                .after(TextInputSet)
+                .run_if(in_state(TextInputState::Inactive))
+                .run_if(in_state(AppState::Display))
+            )
+            // Exports are done last, once any pending geometry or event has been spawned.
+            .add_systems(Last, (
+                export_scene.run_if(not(is_export("json"))),
+                export_event.run_if(is_export("json")),
+            ).run_if(in_state(AppState::Display)));
+    }
+}
+
+fn on_keyboard(keyboard_input: Res<ButtonInput<KeyCode>>) {
+    let extension = if keyboard_input.just_pressed(KeyCode::F5) {
+        "glb"
+    } else if keyboard_input.just_pressed(KeyCode::F6) {
+        "json"
+    } else {
+        return
+    };
+    let now = chrono::Local::now();
+    set_export(format!("calzone-display-{}.{}", now.format("%Y%m%d-%H%M%S"), extension));
+}
+
+/// Run condition on the pending export format.
+fn is_export(extension: &'static str) -> impl Fn() -> bool {
+    move || {
+        EXPORT.lock().unwrap()
+            .as_ref()
+            .and_then(|path| Path::new(path).extension())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+    }
+}
+
+fn export_event(events: Res<Events>) {
+    let Some(path) = EXPORT.lock().unwrap().take() else { return };
+    let event = events.data
+        .get(&events.index)
+        .map(|event| (events.index, event));
+    if let Err(err) = data::phoenix::write(path.as_str(), event) {
+        error!("could not export event to '{}' ({})", path, err);
+    }
+}
+
+type Volumes<'w, 's> = Query<'w, 's, (
+    &'static Volume,
+    &'static Transform,
+    &'static Visibility,
+    Option<&'static Mesh3d>,
+    Option<&'static MeshMaterial3d<StandardMaterial>>,
+    Option<&'static WireframeColor>,
+    Option<&'static Children>,
+)>;
+
+/// Opacity of transparent (wireframe) volumes, when exported.
+const TRANSPARENT_ALPHA: f32 = 0.1;
+
+fn export_scene(
+    roots: Query<Entity, With<RootVolume>>,
+    volumes: Volumes,
+    event: Query<&Children, With<Event>>,
+    tracks: Query<(&Track, &PolylineHandle, &PolylineMaterialHandle, Option<&Children>)>,
+    pieces: Query<&PolylineHandle, With<TrackPiece>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    polylines: Res<Assets<Polyline>>,
+    polymats: Res<Assets<PolylineMaterial>>,
+) {
+    let Some(path) = EXPORT.lock().unwrap().take() else { return };
+
+    fn export_volume( // recursively.
+        entity: Entity,
+        volumes: &Volumes,
+        meshes: &Assets<Mesh>,
+        materials: &Assets<StandardMaterial>,
+        builder: &mut glb::Builder,
+    ) -> Option<usize> {
+        let (volume, transform, visibility, mesh, material, wireframe, children) =
+            volumes.get(entity).ok()?;
+        // Hidden volumes are kept as empty nodes, since their daughters might be visible.
+        let hidden = *visibility == Visibility::Hidden;
+        let mesh = if hidden { None } else { mesh };
+        let color = match material.and_then(|material| materials.get(material)) {
+            Some(material) => Some(material.base_color.to_linear()),
+            None => wireframe.map(|wireframe| {
+                wireframe.color.to_linear().with_alpha(TRANSPARENT_ALPHA)
+            }),
+        };
+        let mesh = mesh.and_then(|mesh| {
+            let color = color.unwrap_or(LinearRgba::WHITE);
+            let material = builder.add_material(color, false);
+            builder.add_mesh(mesh.id(), meshes.get(mesh)?, material)
+        });
+        let children: Vec<usize> = children
+            .map(|children| children
+                .iter()
+                .filter_map(|child| export_volume(child, volumes, meshes, materials, builder))
+                .collect()
+            )
+            .unwrap_or_default();
+        if hidden && children.is_empty() {
+            return None
+        }
+        Some(builder.add_node(volume.name.as_str(), transform, mesh, children))
+    }
+
+    let mut builder = glb::Builder::default();
+    let mut nodes: Vec<usize> = roots
+        .iter()
+        .filter_map(|root| export_volume(root, &volumes, &meshes, &materials, &mut builder))
+        .collect();
+
+    // Overlaid events are exported as separate nodes.
+    for children in event.iter() {
+        let mut index = None;
+        let tracks: Vec<usize> = children
+            .iter()
+            .filter_map(|child| {
+                let (track, polyline, material, children) = tracks.get(child).ok()?;
+                index = Some(track.event);
+                let color = polymats
+                    .get(&material.0)
+                    .map(|material| material.color)
+                    .unwrap_or(LinearRgba::WHITE);
+                let material = builder.add_material(color, true);
+                let name = track.label();
+                let pieces: Vec<&PolylineHandle> = children
+                    .map(|children| children
+                        .iter()
+                        .filter_map(|child| pieces.get(child).ok())
+                        .collect()
+                    )
+                    .unwrap_or_default();
+                if pieces.is_empty() {
+                    let polyline = polylines.get(&polyline.0)?;
+                    let mesh = builder.add_polyline(&polyline.vertices, material)?;
+                    let node = builder.add_node(
+                        name.as_str(), &Transform::IDENTITY, Some(mesh), Vec::new()
+                    );
+                    Some(node)
+                } else {
+                    // Clipped tracks are exported by pieces.
+                    let pieces: Vec<usize> = pieces
+                        .iter()
+                        .filter_map(|piece| {
+                            let polyline = polylines.get(&piece.0)?;
+                            let mesh = builder.add_polyline(&polyline.vertices, material)?;
+                            let node = builder.add_node(
+                                name.as_str(), &Transform::IDENTITY, Some(mesh), Vec::new()
+                            );
+                            Some(node)
+                        })
+                        .collect();
+                    Some(builder.add_node(name.as_str(), &Transform::IDENTITY, None, pieces))
+                }
+            })
+            .collect();
+        if let Some(index) = index && !tracks.is_empty() {
+            let name = format!("Event {}", index);
+            nodes.push(builder.add_node(name.as_str(), &Transform::IDENTITY, None, tracks));
+        }
+    }
+
+    if let Err(err) = builder.write(path.as_str(), nodes) {
+        error!("could not export scene to '{}' ({})", path, err);
+    }
+}
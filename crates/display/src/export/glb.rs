@@ -0,0 +1,249 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+
+// ===============================================================================================
+//
+// Binary glTF writer.
+//
+// ===============================================================================================
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+pub const LINE_STRIP: u32 = 3;
+pub const TRIANGLES: u32 = 4;
+
+#[derive(Default)]
+pub struct Builder {
+    accessors: Vec<Value>,
+    buffer: Vec<u8>,
+    materials: Vec<Value>,
+    materials_index: HashMap<([u32; 4], bool), usize>,
+    meshes: Vec<Value>,
+    meshes_index: HashMap<AssetId<Mesh>, Option<usize>>,
+    nodes: Vec<Value>,
+    views: Vec<Value>,
+}
+
+impl Builder {
+    /// Add a material, returning its index. Identical materials are shared.
+    pub fn add_material(&mut self, color: LinearRgba, unlit: bool) -> usize {
+        let key = (color.to_f32_array().map(f32::to_bits), unlit);
+        if let Some(index) = self.materials_index.get(&key) {
+            return *index
+        }
+        let mut material = json!({
+            "pbrMetallicRoughness": {
+                "baseColorFactor": color.to_f32_array(),
+                "metallicFactor": 0.0,
+                "roughnessFactor": 1.0,
+            },
+            "doubleSided": true,
+        });
+        if color.alpha < 1.0 {
+            material["alphaMode"] = json!("BLEND");
+        }
+        if unlit {
+            material["extensions"] = json!({ "KHR_materials_unlit": {} });
+        }
+        let index = self.materials.len();
+        self.materials.push(material);
+        self.materials_index.insert(key, index);
+        index
+    }
+
+    /// Add a Bevy triangle mesh, returning its index (if not empty). Meshes are shared by asset.
+    pub fn add_mesh(&mut self, id: AssetId<Mesh>, mesh: &Mesh, material: usize) -> Option<usize> {
+        if let Some(index) = self.meshes_index.get(&id) {
+            return *index
+        }
+        let index = self.add_mesh_unchecked(mesh, material);
+        self.meshes_index.insert(id, index);
+        index
+    }
+
+    fn add_mesh_unchecked(&mut self, mesh: &Mesh, material: usize) -> Option<usize> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { return None };
+        if positions.is_empty() {
+            return None
+        }
+        let mut attributes = json!({ "POSITION": self.add_positions(positions) });
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            let view = self.add_view(as_bytes(normals), ARRAY_BUFFER);
+            attributes["NORMAL"] = json!(self.add_accessor(view, FLOAT, normals.len(), "VEC3"));
+        }
+        let mut primitive = json!({
+            "attributes": attributes,
+            "material": material,
+            "mode": TRIANGLES,
+        });
+        if let Some(indices) = mesh.indices() {
+            let indices: Vec<u32> = match indices {
+                Indices::U16(indices) => indices.iter().map(|i| *i as u32).collect(),
+                Indices::U32(indices) => indices.clone(),
+            };
+            let view = self.add_view(as_bytes(&indices), ELEMENT_ARRAY_BUFFER);
+            primitive["indices"] = json!(
+                self.add_accessor(view, UNSIGNED_INT, indices.len(), "SCALAR")
+            );
+        }
+        Some(self.push_mesh(primitive))
+    }
+
+    /// Add a polyline, as a line strip.
+    pub fn add_polyline(&mut self, vertices: &[Vec3], material: usize) -> Option<usize> {
+        if vertices.len() < 2 {
+            return None
+        }
+        let positions: Vec<[f32; 3]> = vertices
+            .iter()
+            .map(|v| v.to_array())
+            .collect();
+        let primitive = json!({
+            "attributes": { "POSITION": self.add_positions(&positions) },
+            "material": material,
+            "mode": LINE_STRIP,
+        });
+        Some(self.push_mesh(primitive))
+    }
+
+    /// Add a node, returning its index.
+    pub fn add_node(
+        &mut self,
+        name: &str,
+        transform: &Transform,
+        mesh: Option<usize>,
+        children: Vec<usize>,
+    ) -> usize {
+        let mut node = json!({ "name": name });
+        if transform.translation != Vec3::ZERO {
+            node["translation"] = json!(transform.translation.to_array());
+        }
+        if transform.rotation != Quat::IDENTITY {
+            node["rotation"] = json!(transform.rotation.to_array());
+        }
+        if transform.scale != Vec3::ONE {
+            node["scale"] = json!(transform.scale.to_array());
+        }
+        if let Some(mesh) = mesh {
+            node["mesh"] = json!(mesh);
+        }
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        let index = self.nodes.len();
+        self.nodes.push(node);
+        index
+    }
+
+    /// Write the scene, with the given root nodes, to a .glb file.
+    pub fn write(mut self, path: &str, roots: Vec<usize>) -> io::Result<()> {
+        let unlit = self.materials.iter().any(|material| material.get("extensions").is_some());
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": "calzone-display" },
+            "scene": 0,
+            "scenes": [ { "nodes": roots } ],
+            "nodes": self.nodes,
+        });
+        if !self.meshes.is_empty() {
+            root["meshes"] = json!(self.meshes);
+            root["materials"] = json!(self.materials);
+            root["accessors"] = json!(self.accessors);
+            root["bufferViews"] = json!(self.views);
+            root["buffers"] = json!([ { "byteLength": self.buffer.len() } ]);
+        }
+        if unlit {
+            root["extensionsUsed"] = json!(["KHR_materials_unlit"]);
+        }
+
+        let mut json = serde_json::to_vec(&root)?;
+        pad(&mut json, b' ');
+        pad(&mut self.buffer, 0);
+        let mut length = 12 + 8 + json.len();
+        if !self.buffer.is_empty() {
+            length += 8 + self.buffer.len();
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(b"glTF")?;
+        writer.write_all(&2_u32.to_le_bytes())?;
+        writer.write_all(&(length as u32).to_le_bytes())?;
+        writer.write_all(&(json.len() as u32).to_le_bytes())?;
+        writer.write_all(b"JSON")?;
+        writer.write_all(&json)?;
+        if !self.buffer.is_empty() {
+            writer.write_all(&(self.buffer.len() as u32).to_le_bytes())?;
+            writer.write_all(b"BIN\0")?;
+            writer.write_all(&self.buffer)?;
+        }
+        writer.flush()
+    }
+
+    fn add_accessor(&mut self, view: usize, component: u32, count: usize, kind: &str) -> usize {
+        let index = self.accessors.len();
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": component,
+            "count": count,
+            "type": kind,
+        }));
+        index
+    }
+
+    fn add_positions(&mut self, positions: &[[f32; 3]]) -> usize {
+        let (min, max) = positions.iter().fold(
+            (Vec3::INFINITY, Vec3::NEG_INFINITY),
+            |(min, max), p| (min.min((*p).into()), max.max((*p).into())),
+        );
+        let view = self.add_view(as_bytes(positions), ARRAY_BUFFER);
+        let index = self.add_accessor(view, FLOAT, positions.len(), "VEC3");
+        self.accessors[index]["min"] = json!(min.to_array()); // Required for positions.
+        self.accessors[index]["max"] = json!(max.to_array());
+        index
+    }
+
+    fn add_view(&mut self, bytes: &[u8], target: u32) -> usize {
+        pad(&mut self.buffer, 0);
+        let index = self.views.len();
+        self.views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(bytes);
+        index
+    }
+
+    fn push_mesh(&mut self, primitive: Value) -> usize {
+        let index = self.meshes.len();
+        self.meshes.push(json!({ "primitives": [ primitive ] }));
+        index
+    }
+}
+
+/// Pad data to a 4 bytes boundary, as required by the glb format.
+fn pad(data: &mut Vec<u8>, value: u8) {
+    while !data.len().is_multiple_of(4) {
+        data.push(value);
+    }
+}
+
+fn as_bytes<T: Copy>(values: &[T]) -> &[u8] {
+    // Safety: only used with plain (little endian) floats and integers.
+    unsafe {
+        std::slice::from_raw_parts(values.as_ptr() as *const u8, size_of_val(values))
+    }
+}
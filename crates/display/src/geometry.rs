@@ -3,7 +3,9 @@ use bevy::color::palettes::css::*;
 use bevy::ecs::system::EntityCommands;
 use bevy::pbr::wireframe::{WireframeColor, WireframePlugin};
 use bevy::render::{mesh::MeshAabb, primitives::Aabb};
+use bevy_rapier3d::prelude::*;
 use crate::app::{AppState, Removable};
+use crate::view_transform;
 use convert_case::{Case, Casing};
 use std::collections::HashMap;
 use std::ops::DerefMut;
@@ -11,13 +13,25 @@ use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 mod bundle;
+mod clipping;
 mod data;
+mod gltf;
 mod jmol;
 mod meshes;
+mod obj;
+mod picking;
+mod ply;
 mod stl;
 mod units;
 
+use data::ToTransform;
+use units::Meters;
+
+pub use clipping::set_clipping;
 pub use data::GeometryInfo;
+pub(crate) use clipping::{Clipping, update_clipping};
+pub(crate) use picking::{Selection, pick_volume};
+pub use stl::LoadSettings;
 
 
 pub(crate) struct GeometryPlugin;
@@ -33,6 +47,24 @@ pub(crate) struct Volume {
     pub name: String,
     pub aabb: Aabb,
     pub expanded: bool,
+    pub visible: bool,
+    /// Set if the volume is entirely clipped.
+    pub clipped: bool,
+    /// Set if the volume is cut away, for viewing a target behind it.
+    pub cut: bool,
+}
+
+/// Solid and material properties of a volume, for data geometries.
+#[derive(Component)]
+pub(crate) struct Properties {
+    pub solid: &'static str,
+    pub parameters: Vec<(&'static str, String)>,
+    pub material: String,
+    /// Material density, in g/cm^3.
+    pub density: f64,
+    pub state: String,
+    /// Mass fractions of the material elements.
+    pub composition: Vec<(String, f64)>,
 }
 
 #[derive(Component)]
@@ -43,9 +75,10 @@ pub(crate) struct Transparent;
 
 #[derive(Default)]
 pub(crate) enum Configuration {
+    Assembly(data::AssemblyInfo),
     Data(Arc<data::GeometryInfo>),
     Close,
-    Stl(String),
+    Mesh(data::MeshFileInfo),
     #[default]
     None,
 }
@@ -61,16 +94,24 @@ pub fn set_data(data: data::GeometryInfo) {
     *GEOMETRY.lock().unwrap() = config;
 }
 
-pub fn set_stl(path: String) {
-    let config = Configuration::Stl(path);
+/// Set a mesh file (STL, OBJ, PLY or glTF).
+pub fn set_mesh(mesh: data::MeshFileInfo) {
+    let config = Configuration::Mesh(mesh);
+    *GEOMETRY.lock().unwrap() = config;
+}
+
+/// Set an assembly of STL parts.
+pub fn set_assembly(assembly: data::AssemblyInfo) {
+    let config = Configuration::Assembly(assembly);
     *GEOMETRY.lock().unwrap() = config;
 }
 
 impl GeometryPlugin{
     pub fn is_data() -> bool {
         match *GEOMETRY.lock().unwrap() {
+            Configuration::Assembly(_) => true,
             Configuration::Data(_) => true,
-            Configuration::Stl(_) => true,
+            Configuration::Mesh(_) => true,
             _ => false,
         }
     }
@@ -87,7 +128,16 @@ impl Plugin for GeometryPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_plugins(WireframePlugin::default())
-            .add_systems(OnEnter(AppState::Display), setup_geometry.in_set(GeometrySet));
+            .init_resource::<Clipping>()
+            .init_resource::<Selection>()
+            .add_systems(OnEnter(AppState::Display), setup_geometry.in_set(GeometrySet))
+            .add_systems(Update, (
+                add_colliders,
+                pick_volume.after(add_colliders),
+                update_clipping,
+                clipping::clip_volumes.after(update_clipping).after(add_colliders),
+                update_visibility.after(clipping::clip_volumes),
+            ).run_if(in_state(AppState::Display)));
     }
 }
 
@@ -151,41 +201,187 @@ fn setup_geometry(
                 &mut materials,
             );
         },
-        Configuration::Stl(path) => {
-            let mesh = stl::load(path.as_str(), None)
+        Configuration::Mesh(mesh) => {
+            let settings = LoadSettings {
+                compute_normal: mesh.compute_normal,
+                interpolate_normal: mesh.interpolate_normal,
+            };
+            let mut objects = load_meshes(mesh.path.as_str(), settings)
                 .unwrap_or_else(|err| panic!("{}", err));
-            let aabb = mesh.compute_aabb().unwrap();
-            let name = Path::new(path.as_str())
+            for object in objects.iter_mut() {
+                object.mesh.scale_by(Vec3::splat(mesh.unit.meters()));
+            }
+            let name = Path::new(mesh.path.as_str())
                 .file_stem()
                 .unwrap()
                 .to_str()
                 .unwrap()
                 .to_string()
                 .to_case(Case::Pascal);
-            let color = SADDLE_BROWN.into();
-            commands.spawn((
-                Mesh3d(meshes.add(mesh)),
-                MeshMaterial3d(materials.add(StandardMaterial {
-                    base_color: color,
-                    cull_mode: None,
-                    ..default()
-                })),
-                WireframeColor { color },
-                RootVolume,
-                Removable,
-                Plain,
-                Volume::new(name, aabb),
-            ));
+            spawn_meshes(name, objects, &mut commands, &mut meshes, &mut materials);
+        },
+        Configuration::Assembly(assembly) => {
+            let settings = LoadSettings {
+                compute_normal: assembly.compute_normal,
+                interpolate_normal: assembly.interpolate_normal,
+            };
+            let objects = assembly.parts
+                .iter()
+                .map(|part| load_part(part, settings))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_or_else(|err| panic!("{}", err));
+            if objects.is_empty() {
+                panic!("no part found in '{}' assembly", assembly.name);
+            }
+            let name = assembly.name.clone();
+            spawn_meshes(name, objects, &mut commands, &mut meshes, &mut materials);
         },
         Configuration::Close => (),
         Configuration::None => (),
     }
 }
 
+/// Attach trimesh colliders to new volumes, for picking them by ray casting.
+fn add_colliders(
+    volumes: Query<(Entity, &Mesh3d), Added<Volume>>,
+    meshes: Res<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    for (entity, mesh) in volumes.iter() {
+        let Some(mesh) = meshes.get(&mesh.0) else { continue };
+        let shape = ComputedColliderShape::TriMesh(TriMeshFlags::default());
+        let Some(collider) = Collider::from_bevy_mesh(mesh, &shape) else { continue };
+        commands.entity(entity).insert(collider);
+    }
+}
+
+/// Apply volumes visibility. Visible volumes are explicitly shown, such that daughters of hidden
+/// volumes remain visible.
+pub(crate) fn update_visibility(
+    mut volumes: Query<(&Volume, &mut Visibility), Changed<Volume>>,
+) {
+    for (volume, mut visibility) in volumes.iter_mut() {
+        let new_visibility = if volume.visible && !volume.clipped && !volume.cut {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        visibility.set_if_neq(new_visibility);
+    }
+}
+
+/// Load an assembly part, converted to meters and placed in the view frame.
+fn load_part(
+    part: &data::PartInfo,
+    settings: LoadSettings,
+) -> Result<meshes::NamedMesh, std::io::Error> {
+    let mut mesh = stl::load(part.path.as_str(), Some(settings))
+        .map_err(|err| std::io::Error::new(
+            err.kind(),
+            format!("could not load '{}' ({})", part.path, err),
+        ))?;
+    mesh.scale_by(Vec3::splat(part.unit.meters()));
+    mesh.transform_by(part.transform.to_transform() * view_transform());
+    let color = part.color.map(|[r, g, b]| Color::srgb(r, g, b));
+    Ok(meshes::NamedMesh { name: part.name.clone(), mesh, color })
+}
+
+fn load_meshes(
+    path: &str,
+    settings: LoadSettings,
+) -> Result<Vec<meshes::NamedMesh>, std::io::Error> {
+    let file = Path::new(path);
+    let name = || file
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Mesh")
+        .to_case(Case::Pascal);
+    let extension = file
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+    let objects = match extension.as_deref() {
+        Some("glb") | Some("gltf") => gltf::load(path)?,
+        Some("obj") => obj::load(path)?,
+        Some("ply") => ply::load(path, name())?,
+        _ => {
+            let mesh = stl::load(path, Some(settings))?;
+            vec![meshes::NamedMesh { name: name(), mesh, color: None }]
+        },
+    };
+    if objects.is_empty() {
+        let msg = format!("no mesh found in '{}'", path);
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, msg))
+    }
+    Ok(objects)
+}
+
+/// Spawn loaded meshes, grouped under a common root volume if there are several of them.
+fn spawn_meshes(
+    name: String,
+    mut objects: Vec<meshes::NamedMesh>,
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let mut spawn_object = |object: meshes::NamedMesh, name: String| {
+        let aabb = object.mesh.compute_aabb().unwrap();
+        let color = object.color.unwrap_or_else(|| SADDLE_BROWN.into());
+        let bundle = (
+            Mesh3d(meshes.add(object.mesh)),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: color,
+                cull_mode: None,
+                ..default()
+            })),
+            WireframeColor { color },
+            Plain,
+            Volume::new(name, aabb),
+        );
+        (bundle, aabb)
+    };
+
+    if objects.len() == 1 {
+        let object = objects.pop().unwrap();
+        let (bundle, _) = spawn_object(object, name);
+        commands.spawn((bundle, RootVolume, Removable));
+    } else {
+        let mut min = Vec3::INFINITY;
+        let mut max = Vec3::NEG_INFINITY;
+        let bundles: Vec<_> = objects
+            .drain(..)
+            .map(|object| {
+                let name = object.name.clone();
+                let (bundle, aabb) = spawn_object(object, name);
+                min = min.min(aabb.min().into());
+                max = max.max(aabb.max().into());
+                bundle
+            })
+            .collect();
+        let aabb = Aabb::from_min_max(min, max);
+        commands
+            .spawn((
+                Transform::default(),
+                Visibility::default(),
+                RootVolume,
+                Removable,
+                Volume::new(name, aabb),
+            ))
+            .with_children(|parent| {
+                for bundle in bundles {
+                    parent.spawn(bundle);
+                }
+            });
+    }
+}
+
 impl Volume {
     fn new(name: String, aabb: Aabb) -> Self {
         let expanded = false;
-        Self { name, aabb, expanded }
+        let visible = true;
+        let clipped = false;
+        let cut = false;
+        Self { name, aabb, expanded, visible, clipped, cut }
     }
 
     pub fn target(&self) -> Transform {
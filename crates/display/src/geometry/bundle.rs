@@ -4,7 +4,7 @@ use bevy::pbr::wireframe::WireframeColor;
 use bevy::render::mesh::VertexAttributeValues;
 use bevy::render::primitives::Aabb;
 use crate::app::Removable;
-use super::data::{Color, MaterialInfo, ToTransform, VolumeInfo};
+use super::data::{Color, MaterialInfo, Parameters, ToTransform, VolumeInfo};
 use super::meshes::IntoMesh;
 use std::collections::HashMap;
 use std::sync::{LazyLock, Mutex};
@@ -13,6 +13,7 @@ use std::sync::{LazyLock, Mutex};
 pub enum VolumeSpawner {
     Standard {
         volume: super::Volume,
+        properties: super::Properties,
         mesh: Mesh3d,
         material: MeshMaterial3d<StandardMaterial>,
         transform: Transform,
@@ -20,6 +21,7 @@ pub enum VolumeSpawner {
     },
     Wireframe {
         volume: super::Volume,
+        properties: super::Properties,
         mesh: Mesh3d,
         transform: Transform,
         color: bevy::prelude::Color,
@@ -35,6 +37,15 @@ impl VolumeSpawner {
         standards: &mut Assets<StandardMaterial>,
     ) -> Self {
         let material = materials.get(info.material.as_str()).unwrap();
+        let (solid, parameters) = info.solid.parameters();
+        let properties = super::Properties {
+            solid,
+            parameters,
+            material: info.material.clone(),
+            density: material.density,
+            state: material.state.clone(),
+            composition: material.composition.clone(),
+        };
         let mesh = info.solid.into_mesh();
         let color = material.color().into();
         let transform = info.transform.to_transform();
@@ -43,7 +54,7 @@ impl VolumeSpawner {
         let mesh = Mesh3d(meshes.add(mesh));
         let volume = super::Volume::new(info.name, aabb);
         if (material.state.as_str() == "gas") || (material.density <= 1E-02) {
-            Self::Wireframe { volume, mesh, transform, color }
+            Self::Wireframe { volume, properties, mesh, transform, color }
         } else {
             let material = MeshMaterial3d(STANDARD_MATERIALS.lock().unwrap()
                 .entry(info.material)
@@ -55,20 +66,22 @@ impl VolumeSpawner {
                         ..default()
                     })
                 }).clone());
-            Self::Standard { volume, mesh, material, transform, color }
+            Self::Standard { volume, properties, mesh, material, transform, color }
         }
     }
 
     pub fn spawn_child<'a>(self, parent: &'a mut ChildSpawnerCommands) -> EntityCommands<'a> {
         match self {
-            Self::Standard { volume, mesh, material, transform, color } => {
+            Self::Standard { volume, properties, mesh, material, transform, color } => {
                 parent.spawn((
-                    volume, mesh, material, transform, WireframeColor { color }, super::Plain,
+                    volume, properties, mesh, material, transform, WireframeColor { color },
+                    super::Plain,
                 ))
             },
-            Self::Wireframe { volume, mesh, transform, color } => {
+            Self::Wireframe { volume, properties, mesh, transform, color } => {
                 parent.spawn((
-                    volume, mesh, transform, WireframeColor { color }, super::Transparent,
+                    volume, properties, mesh, transform, WireframeColor { color },
+                    super::Transparent,
                 ))
             },
         }
@@ -76,16 +89,16 @@ impl VolumeSpawner {
 
     pub fn spawn_root<'a>(self, commands: &'a mut Commands) -> EntityCommands<'a> {
         match self {
-            Self::Standard { volume, mesh, material, transform, color } => {
+            Self::Standard { volume, properties, mesh, material, transform, color } => {
                 commands.spawn((
-                    volume, mesh, material, transform, WireframeColor { color }, super::RootVolume,
-                    super::Plain, Removable
+                    volume, properties, mesh, material, transform, WireframeColor { color },
+                    super::RootVolume, super::Plain, Removable
                 ))
             },
-            Self::Wireframe { volume, mesh, transform, color } => {
+            Self::Wireframe { volume, properties, mesh, transform, color } => {
                 commands.spawn((
-                    volume, mesh, transform, WireframeColor { color }, super::RootVolume,
-                    super::Transparent, Removable
+                    volume, properties, mesh, transform, WireframeColor { color },
+                    super::RootVolume, super::Transparent, Removable
                 ))
             },
         }
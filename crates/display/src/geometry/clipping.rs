@@ -0,0 +1,276 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy_rapier3d::na::{Point3, Unit, Vector3};
+use bevy_rapier3d::parry::query::SplitResult;
+use bevy_rapier3d::parry::shape::{TriMesh, TriMeshFlags};
+use bevy_rapier3d::prelude::*;
+use crate::drone::DroneCamera;
+use crate::{view_to_world, world_to_view};
+use std::sync::Mutex;
+use super::units::Meters;
+use super::Volume;
+
+
+// ===============================================================================================
+//
+// Clipping planes.
+//
+// ===============================================================================================
+
+static CLIPPING: Mutex<Option<data::geometry::Clipping>> = Mutex::new(None);
+
+/// Set the clipping planes, or remove them if there are none.
+pub fn set_clipping(clipping: data::geometry::Clipping) {
+    *CLIPPING.lock().unwrap() = Some(clipping);
+}
+
+/// The current clipping planes. Camera planes are resolved to fixed ones when set.
+#[derive(Default, Resource)]
+pub(crate) struct Clipping {
+    pub settings: data::geometry::Clipping,
+    planes: Vec<Plane>,
+}
+
+/// A plane in the view frame, clipping points such that `normal · x > offset`.
+#[derive(Clone, Copy)]
+struct Plane {
+    normal: Vec3,
+    offset: f32,
+}
+
+/// The original mesh of a clipped volume.
+#[derive(Component)]
+pub(crate) struct Unclipped(Handle<Mesh>);
+
+enum Clip {
+    Inside,
+    Outside,
+    Cut(Mesh),
+}
+
+impl Clipping {
+    /// Check if tracks are clipped.
+    pub fn tracks(&self) -> bool {
+        self.settings.tracks && !self.planes.is_empty()
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.planes.iter().all(|plane| plane.normal.dot(point) <= plane.offset)
+    }
+
+    /// Unclipped part of the segment [a, b], as a range of its parameter.
+    pub fn clip_segment(&self, a: Vec3, b: Vec3) -> Option<(f32, f32)> {
+        let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+        for plane in self.planes.iter() {
+            let da = plane.normal.dot(a) - plane.offset;
+            let db = plane.normal.dot(b) - plane.offset;
+            if (da > 0.0) && (db > 0.0) {
+                return None
+            } else if (da <= 0.0) && (db <= 0.0) {
+                continue
+            }
+            let t = da / (da - db);
+            if da > 0.0 {
+                t0 = t0.max(t);
+            } else {
+                t1 = t1.min(t);
+            }
+        }
+        (t0 <= t1).then_some((t0, t1))
+    }
+
+    /// Unclipped pieces of a polyline, with the index of their first segment.
+    pub fn clip_polyline(&self, vertices: &[Vec3]) -> Vec<(usize, Vec<Vec3>)> {
+        let mut pieces: Vec<(usize, Vec<Vec3>)> = Vec::new();
+        let mut open = false;
+        for (index, segment) in vertices.windows(2).enumerate() {
+            let (a, b) = (segment[0], segment[1]);
+            let Some((t0, t1)) = self.clip_segment(a, b) else {
+                open = false;
+                continue
+            };
+            let (pa, pb) = (a.lerp(b, t0), a.lerp(b, t1));
+            match pieces.last_mut() {
+                Some((_, piece)) if open && (t0 == 0.0) => piece.push(pb),
+                _ => pieces.push((index, vec![pa, pb])),
+            }
+            open = t1 == 1.0;
+        }
+        pieces
+    }
+
+    /// Clip a volume mesh, closing cut faces with caps if the mesh is watertight.
+    fn clip_mesh(&self, mesh: &Mesh, transform: &GlobalTransform) -> Clip {
+        const EPSILON: f32 = 1E-06;
+
+        if self.planes.is_empty() {
+            return Clip::Inside
+        }
+        let Some(mut trimesh) = to_trimesh(mesh) else { return Clip::Inside };
+        let affine = transform.affine();
+        let mut cut = false;
+        for plane in self.planes.iter() {
+            // Express the plane in the mesh frame.
+            let normal = affine.matrix3.transpose() * plane.normal;
+            let length = normal.length();
+            if length <= 0.0 {
+                continue
+            }
+            let bias = (plane.offset - plane.normal.dot(affine.translation.into())) / length;
+            let normal = Unit::new_normalize(Vector3::new(normal.x, normal.y, normal.z));
+            match trimesh.local_split(&normal, bias, EPSILON) {
+                SplitResult::Negative => (),
+                SplitResult::Positive => return Clip::Outside,
+                SplitResult::Pair(inside, _) => {
+                    trimesh = with_caps(inside);
+                    cut = true;
+                },
+            }
+        }
+        if cut { Clip::Cut(to_mesh(&trimesh)) } else { Clip::Inside }
+    }
+}
+
+/// Apply any pending clipping planes, resolving camera planes to fixed ones.
+pub(crate) fn update_clipping(
+    mut clipping: ResMut<Clipping>,
+    camera: Query<&GlobalTransform, With<DroneCamera>>,
+) {
+    let Some(mut settings) = CLIPPING.lock().unwrap().take() else { return };
+    let camera = camera.single().ok();
+    let mut planes = Vec::new();
+    settings.planes.retain_mut(|plane| {
+        let resolved = match plane.normal {
+            data::geometry::Normal::Vector(normal) => {
+                let normal = world_to_view().transform_vector3(
+                    Vec3::from(normal.map(|x| x as f32)).normalize_or_zero()
+                );
+                Plane { normal, offset: plane.offset.meters() }
+            },
+            data::geometry::Normal::Camera => {
+                let Some(camera) = camera else { return false };
+                let forward = camera.forward();
+                let point = camera.translation() + forward * plane.offset.meters();
+                let normal = -forward.as_vec3();
+                let offset = normal.dot(point);
+                let world = view_to_world().transform_vector3(normal);
+                plane.normal = data::geometry::Normal::Vector(world.to_array().map(|x| x as f64));
+                plane.offset = (offset as f64) * 1E+03;
+                Plane { normal, offset }
+            },
+        };
+        if resolved.normal != Vec3::ZERO {
+            planes.push(resolved);
+            true
+        } else {
+            false
+        }
+    });
+    clipping.settings = settings;
+    clipping.planes = planes;
+}
+
+/// Clip volumes when the planes change, or when volumes are placed.
+pub(crate) fn clip_volumes(
+    clipping: Res<Clipping>,
+    volumes: Query<(Entity, &Mesh3d, &GlobalTransform, Option<&Unclipped>), With<Volume>>,
+    moved: Query<(), (With<Volume>, Changed<GlobalTransform>)>,
+    mut states: Query<&mut Volume>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut commands: Commands,
+) {
+    if !clipping.is_changed() && (clipping.planes.is_empty() || moved.is_empty()) {
+        return
+    }
+    for (entity, mesh, transform, unclipped) in volumes.iter() {
+        if !clipping.is_changed() && !moved.contains(entity) {
+            continue
+        }
+        let original = unclipped
+            .map(|unclipped| unclipped.0.clone())
+            .unwrap_or_else(|| mesh.0.clone());
+        let Some(source) = meshes.get(&original) else { continue };
+        let (handle, outside) = match clipping.clip_mesh(source, transform) {
+            Clip::Inside => (original.clone(), false),
+            Clip::Outside => (original.clone(), true),
+            Clip::Cut(clipped) => (meshes.add(clipped), false),
+        };
+
+        if let Ok(mut volume) = states.get_mut(entity) && (volume.clipped != outside) {
+            volume.clipped = outside;
+        }
+        if handle == mesh.0 {
+            continue
+        }
+        let mut entity = commands.entity(entity);
+        if handle == original {
+            entity.remove::<Unclipped>();
+        } else {
+            entity.insert(Unclipped(original));
+        }
+        let shape = ComputedColliderShape::TriMesh(TriMeshFlags::default());
+        if let Some(collider) = meshes
+            .get(&handle)
+            .and_then(|mesh| Collider::from_bevy_mesh(mesh, &shape)) {
+            entity.insert(collider);
+        }
+        entity.insert(Mesh3d(handle));
+    }
+}
+
+fn to_trimesh(mesh: &Mesh) -> Option<TriMesh> {
+    let VertexAttributeValues::Float32x3(positions) = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?
+    else {
+        return None
+    };
+    let vertices: Vec<_> = positions
+        .iter()
+        .map(|[x, y, z]| Point3::new(*x, *y, *z))
+        .collect();
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(indices) => indices.iter().map(|index| index as u32).collect(),
+        None => (0..(vertices.len() as u32)).collect(),
+    };
+    let indices: Vec<[u32; 3]> = indices
+        .chunks_exact(3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .collect();
+    let trimesh = TriMesh::with_flags(vertices.clone(), indices.clone(), CAPS_FLAGS)
+        .or_else(|_| TriMesh::new(vertices, indices))
+        .ok()?;
+    Some(trimesh)
+}
+
+/// Flags required for capping cut faces, i.e. a closed and oriented mesh.
+const CAPS_FLAGS: TriMeshFlags = TriMeshFlags::MERGE_DUPLICATE_VERTICES
+    .union(TriMeshFlags::DELETE_DEGENERATE_TRIANGLES)
+    .union(TriMeshFlags::DELETE_DUPLICATE_TRIANGLES)
+    .union(TriMeshFlags::HALF_EDGE_TOPOLOGY)
+    .union(TriMeshFlags::ORIENTED);
+
+/// Restore the orientation of a cut mesh, such that it can be capped again by further planes.
+fn with_caps(trimesh: TriMesh) -> TriMesh {
+    TriMesh::with_flags(trimesh.vertices().to_vec(), trimesh.indices().to_vec(), CAPS_FLAGS)
+        .unwrap_or(trimesh)
+}
+
+fn to_mesh(trimesh: &TriMesh) -> Mesh {
+    let positions: Vec<[f32; 3]> = trimesh
+        .vertices()
+        .iter()
+        .map(|vertex| [vertex.x, vertex.y, vertex.z])
+        .collect();
+    let indices: Vec<u32> = trimesh
+        .indices()
+        .iter()
+        .flatten()
+        .copied()
+        .collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_indices(Indices::U32(indices));
+    mesh.duplicate_vertices();
+    mesh.compute_flat_normals();
+    mesh
+}
@@ -6,8 +6,8 @@ use crate::{view_to_world, world_to_view};
 use super::units::Meters;
 
 pub use data::geometry::{
-    GeometryInfo, VolumeInfo, SolidInfo, BoxInfo, OrbInfo, SphereInfo, MeshInfo, TransformInfo,
-    TubsInfo, MaterialInfo,
+    AssemblyInfo, GeometryInfo, VolumeInfo, SolidInfo, BoxInfo, OrbInfo, SphereInfo, MeshInfo,
+    MeshFileInfo, TransformInfo, TubsInfo, MaterialInfo, PartInfo,
 };
 
 pub(crate) trait ToTransform {
@@ -49,3 +49,62 @@ impl Color for MaterialInfo {
         Srgba::new(color[0], color[1], color[2], 1.0)
     }
 }
+
+pub(crate) trait Parameters {
+    /// Solid type and parameters, formatted for display.
+    fn parameters(&self) -> (&'static str, Vec<(&'static str, String)>);
+}
+
+impl Parameters for SolidInfo {
+    fn parameters(&self) -> (&'static str, Vec<(&'static str, String)>) {
+        let length = |x: f64| format!("{} mm", x);
+        let angle = |x: f64| format!("{} deg", x.to_degrees());
+        let vector = |x: &[f64; 3]| format!("{}, {}, {} mm", x[0], x[1], x[2]);
+        let displacement = |parameters: &mut Vec<_>, x: &[f64; 3]| {
+            if x.iter().any(|xi| *xi != 0.0) {
+                parameters.push(("displacement", vector(x)));
+            }
+        };
+
+        let mut parameters = Vec::new();
+        let solid = match self {
+            SolidInfo::Box(solid) => {
+                parameters.push(("size", vector(&solid.size)));
+                displacement(&mut parameters, &solid.displacement);
+                "Box"
+            },
+            SolidInfo::Mesh(solid) => {
+                parameters.push(("facets", (solid.0.len() / 9).to_string()));
+                "Mesh"
+            },
+            SolidInfo::Orb(solid) => {
+                parameters.push(("radius", length(solid.radius)));
+                displacement(&mut parameters, &solid.displacement);
+                "Orb"
+            },
+            SolidInfo::Sphere(solid) => {
+                if solid.inner_radius > 0.0 {
+                    parameters.push(("inner radius", length(solid.inner_radius)));
+                }
+                parameters.push(("outer radius", length(solid.outer_radius)));
+                parameters.push(("start phi", angle(solid.start_phi)));
+                parameters.push(("delta phi", angle(solid.delta_phi)));
+                parameters.push(("start theta", angle(solid.start_theta)));
+                parameters.push(("delta theta", angle(solid.delta_theta)));
+                "Sphere"
+            },
+            SolidInfo::Tubs(solid) => {
+                if solid.inner_radius > 0.0 {
+                    parameters.push(("inner radius", length(solid.inner_radius)));
+                }
+                parameters.push(("outer radius", length(solid.outer_radius)));
+                parameters.push(("length", length(solid.length)));
+                parameters.push(("start phi", angle(solid.start_phi)));
+                parameters.push(("delta phi", angle(solid.delta_phi)));
+                displacement(&mut parameters, &solid.displacement);
+                "Tubs"
+            },
+        };
+        (solid, parameters)
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use std::io::{Error, ErrorKind};
+use super::meshes::{IntoMesh, MeshData, NamedMesh};
+
+
+/// Load a glTF (or GLB) file, with one mesh per node.
+pub fn load(path: &str) -> Result<Vec<NamedMesh>, Error> {
+    let (document, buffers, _) = ::gltf::import(path)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+    let scene = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing glTF scene"))?;
+
+    let mut meshes = Vec::new();
+    for node in scene.nodes() {
+        visit(node, Mat4::IDENTITY, &buffers, &mut meshes)?;
+    }
+    Ok(meshes)
+}
+
+fn visit(
+    node: ::gltf::Node,
+    parent: Mat4,
+    buffers: &[::gltf::buffer::Data],
+    meshes: &mut Vec<NamedMesh>,
+) -> Result<(), Error> {
+    let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        let mut color = None;
+        for primitive in mesh.primitives() {
+            if primitive.mode() != ::gltf::mesh::Mode::Triangles {
+                continue
+            }
+            let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+            let Some(vertices) = reader.read_positions() else { continue };
+            let offset = positions.len() as u32;
+            positions.extend(vertices.map(|v| transform.transform_point3(v.into()).to_array()));
+            match reader.read_indices() {
+                Some(read) => indices.extend(read.into_u32().map(|i| i + offset)),
+                None => indices.extend(offset..(positions.len() as u32)),
+            }
+            let material = primitive.material();
+            if color.is_none() && material.index().is_some() {
+                let [r, g, b, _] = material.pbr_metallic_roughness().base_color_factor();
+                color = Some(Color::linear_rgb(r, g, b));
+            }
+        }
+
+        if !indices.is_empty() {
+            let name = node.name()
+                .or_else(|| mesh.name())
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| format!("Node{}", node.index()));
+            let mesh = MeshData::from_triangles(&positions, &indices)?.into_mesh();
+            meshes.push(NamedMesh { name, mesh, color });
+        }
+    }
+
+    for child in node.children() {
+        visit(child, transform, buffers, meshes)?;
+    }
+    Ok(())
+}
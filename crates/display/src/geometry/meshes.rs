@@ -6,6 +6,7 @@ use bevy::render::render_asset::RenderAssetUsages;
 use crate::view_transform;
 use super::data::{BoxInfo, MeshInfo, OrbInfo, SolidInfo, SphereInfo, TubsInfo};
 use super::units::Meters;
+use std::io::{Error, ErrorKind};
 
 pub(crate) trait IntoMesh {
     fn into_mesh(self) -> Mesh;
@@ -186,6 +187,25 @@ pub struct MeshData {
 }
 
 impl MeshData {
+    /// Build flat shaded mesh data from indexed triangles, checking indices.
+    pub fn from_triangles(positions: &[[f32; 3]], indices: &[u32]) -> Result<Self, Error> {
+        if indices.iter().any(|index| (*index as usize) >= positions.len()) {
+            let msg = "bad mesh (vertex index out of range)";
+            return Err(Error::new(ErrorKind::InvalidData, msg))
+        }
+        let n = indices.len();
+        let mut vertices = Vec::with_capacity(n); // Vertices are duplicated in order to properly
+        let mut normals = Vec::with_capacity(n);  // apply faces normals.
+        for triangle in indices.chunks_exact(3) {
+            let v: [[f32; 3]; 3] = std::array::from_fn(|j| positions[triangle[j] as usize]);
+            let normal: [f32; 3] = Self::compute_normal(&v).into();
+            vertices.extend_from_slice(&v);
+            normals.extend_from_slice(&[normal; 3]);
+        }
+        let indices = (0..(vertices.len() as u32)).collect();
+        Ok(Self { vertices, normals, indices })
+    }
+
     pub fn compute_normal<T>(vertices: &[T; 3]) -> Vec3
     where
         T: Copy,
@@ -214,6 +234,13 @@ impl IntoMesh for MeshData {
     }
 }
 
+/// A mesh loaded from a file, e.g. an OBJ object or a glTF node.
+pub struct NamedMesh {
+    pub name: String,
+    pub mesh: Mesh,
+    pub color: Option<Color>,
+}
+
 #[derive(Clone, Copy)]
 struct AnnulusSector {
     inner_radius: f32,
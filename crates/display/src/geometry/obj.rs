@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use std::io::{Error, ErrorKind};
+use super::meshes::{IntoMesh, MeshData, NamedMesh};
+
+
+/// Load a Wavefront OBJ file, with one mesh per object.
+pub fn load(path: &str) -> Result<Vec<NamedMesh>, Error> {
+    let options = tobj::LoadOptions {
+        single_index: true,
+        triangulate: true,
+        ..default()
+    };
+    let (models, materials) = tobj::load_obj(path, &options)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+    let materials = materials.unwrap_or_default(); // Materials (.mtl) are optional.
+
+    models
+        .iter()
+        .enumerate()
+        .filter(|(_, model)| !model.mesh.indices.is_empty())
+        .map(|(i, model)| {
+            let positions: Vec<[f32; 3]> = model.mesh.positions
+                .chunks_exact(3)
+                .map(|v| [v[0], v[1], v[2]])
+                .collect();
+            let mesh = MeshData::from_triangles(&positions, &model.mesh.indices)?.into_mesh();
+            let name = if model.name.is_empty() {
+                format!("Object{}", i)
+            } else {
+                model.name.clone()
+            };
+            let color = model.mesh.material_id
+                .and_then(|id| materials.get(id))
+                .and_then(|material| material.diffuse)
+                .map(|[r, g, b]| Color::srgb(r, g, b));
+            Ok(NamedMesh { name, mesh, color })
+        })
+        .collect()
+}
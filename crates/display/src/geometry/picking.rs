@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_rapier3d::prelude::*;
+use crate::drone::DroneCamera;
+use crate::ui::{UiRoot, UiVolume};
+use super::{Plain, Properties, Volume};
+
+
+/// The picked volume, if any.
+#[derive(Default, Resource)]
+pub(crate) struct Selection(pub Option<Entity>);
+
+/// Pick the volume under the cursor on a left click, and show its properties.
+pub(crate) fn pick_volume(
+    buttons: Res<ButtonInput<MouseButton>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    uis: Query<(&ComputedNode, &GlobalTransform), With<UiRoot>>,
+    camera: Query<(&Camera, &GlobalTransform), With<DroneCamera>>,
+    rapier: ReadRapierContext,
+    volumes: Query<(&Volume, Option<&Properties>, Has<Plain>)>,
+    parents: Query<&ChildOf, With<Volume>>,
+    visibilities: Query<&InheritedVisibility>,
+    ui_volume: Query<Entity, With<UiVolume>>,
+    mut selection: ResMut<Selection>,
+    mut commands: Commands,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return
+    }
+    let Ok(window) = window.single() else { return };
+    let Some(cursor) = window.cursor_position() else { return };
+    for (node, transform) in uis.iter() {
+        let rect = Rect::from_center_size(
+            transform.translation().xy(),
+            node.size,
+        );
+        if rect.contains(cursor) {
+            return
+        }
+    }
+
+    for entity in ui_volume.iter() {
+        commands.entity(entity).despawn();
+    }
+    let Ok((camera, camera_transform)) = camera.single() else { return };
+    let Ok(context) = rapier.single() else { return };
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor) else { return };
+
+    // Collect all visible volumes along the ray, since volumes are nested.
+    let predicate = |entity: Entity| visibilities
+        .get(entity)
+        .is_ok_and(|visibility| visibility.get());
+    let filter = QueryFilter::default().predicate(&predicate);
+    let mut hits: Vec<(Entity, f32, bool)> = Vec::new();
+    context.intersections_with_ray(
+        ray.origin, *ray.direction, f32::MAX, false, filter,
+        |entity, intersection| {
+            if let Ok((.., plain)) = volumes.get(entity) {
+                hits.push((entity, intersection.time_of_impact, plain));
+            }
+            true
+        },
+    );
+    hits.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    // Prefer the first opaque volume, since transparent ones are seen through.
+    let picked = hits
+        .iter()
+        .find(|(.., plain)| *plain)
+        .or_else(|| hits.first())
+        .map(|(entity, ..)| *entity);
+    selection.0 = picked;
+    let Some(entity) = picked else { return };
+
+    let Ok((volume, properties, _)) = volumes.get(entity) else { return };
+    let mut names: Vec<&str> = parents
+        .iter_ancestors(entity)
+        .filter_map(|ancestor| volumes.get(ancestor).ok())
+        .map(|(volume, ..)| volume.name.as_str())
+        .collect();
+    names.reverse();
+    names.push(volume.name.as_str());
+    let path = names.join(".");
+
+    UiVolume::spawn_info(&mut commands, cursor, &volume.name, &path, properties);
+}
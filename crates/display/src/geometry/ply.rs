@@ -0,0 +1,350 @@
+use bevy::prelude::*;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read};
+use super::meshes::{IntoMesh, MeshData, NamedMesh};
+
+
+/// Load a PLY file (ascii or binary), as a single mesh.
+///
+/// Polygonal faces are triangulated as fans. Vertex colors, if any, are averaged.
+pub fn load(path: &str, name: String) -> Result<Vec<NamedMesh>, Error> {
+    let reader = BufReader::new(File::open(path)?);
+    let mesh = read(reader, name)?;
+    Ok(vec![mesh])
+}
+
+fn read<R: BufRead>(mut reader: R, name: String) -> Result<NamedMesh, Error> {
+    let header = Header::read(&mut reader)?;
+    let mut body = match header.format {
+        Format::Ascii => {
+            let mut text = String::new();
+            reader.read_to_string(&mut text)?;
+            Body::Ascii(text.split_ascii_whitespace().map(str::to_string).collect(), 0)
+        },
+        Format::Binary { big_endian } => Body::Binary(reader, big_endian),
+    };
+
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    let mut color = Vec4::ZERO;
+    let mut colored = 0_usize;
+    for element in header.elements.iter() {
+        for _ in 0..element.count {
+            let mut position = [0.0_f32; 3];
+            let mut rgb = [None; 3];
+            for property in element.properties.iter() {
+                match property.kind {
+                    Kind::Scalar(ty) => {
+                        let value = body.read(ty)?;
+                        if element.name != "vertex" {
+                            continue
+                        }
+                        match property.name.as_str() {
+                            "x" => position[0] = value as f32,
+                            "y" => position[1] = value as f32,
+                            "z" => position[2] = value as f32,
+                            "red" => rgb[0] = Some(ty.normalise(value)),
+                            "green" => rgb[1] = Some(ty.normalise(value)),
+                            "blue" => rgb[2] = Some(ty.normalise(value)),
+                            _ => (),
+                        }
+                    },
+                    Kind::List(count, ty) => {
+                        let n = body.read(count)? as usize;
+                        let mut polygon = Vec::with_capacity(n.min(16));
+                        for _ in 0..n {
+                            polygon.push(body.read(ty)? as u32);
+                        }
+                        let is_face = (element.name == "face") &&
+                            ((property.name == "vertex_indices") ||
+                             (property.name == "vertex_index"));
+                        if is_face {
+                            for i in 1..n.saturating_sub(1) {
+                                indices.extend_from_slice(
+                                    &[polygon[0], polygon[i], polygon[i + 1]]
+                                );
+                            }
+                        }
+                    },
+                }
+            }
+            if element.name == "vertex" {
+                positions.push(position);
+                if let [Some(r), Some(g), Some(b)] = rgb {
+                    color += Vec4::new(r, g, b, 1.0);
+                    colored += 1;
+                }
+            }
+        }
+    }
+
+    let mesh = MeshData::from_triangles(&positions, &indices)?.into_mesh();
+    let color = (colored > 0).then(|| {
+        let color = color / colored as f32;
+        Color::srgb(color.x, color.y, color.z)
+    });
+    Ok(NamedMesh { name, mesh, color })
+}
+
+fn bad_ply(reason: &str) -> Error {
+    let msg = format!("bad ply ({})", reason);
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+
+// ===============================================================================================
+//
+// PLY header.
+//
+// ===============================================================================================
+
+struct Header {
+    format: Format,
+    elements: Vec<Element>,
+}
+
+enum Format {
+    Ascii,
+    Binary { big_endian: bool },
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+struct Property {
+    name: String,
+    kind: Kind,
+}
+
+enum Kind {
+    Scalar(Type),
+    List(Type, Type),
+}
+
+#[derive(Clone, Copy)]
+enum Type {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Header {
+    fn read<R: BufRead>(reader: &mut R) -> Result<Self, Error> {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim() != "ply" {
+            return Err(bad_ply("missing magic number"))
+        }
+
+        let mut format = None;
+        let mut elements: Vec<Element> = Vec::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Err(bad_ply("missing end of header"))
+            }
+            let words: Vec<&str> = line.split_ascii_whitespace().collect();
+            match words.as_slice() {
+                ["format", "ascii", _] => format = Some(Format::Ascii),
+                ["format", "binary_little_endian", _] => {
+                    format = Some(Format::Binary { big_endian: false })
+                },
+                ["format", "binary_big_endian", _] => {
+                    format = Some(Format::Binary { big_endian: true })
+                },
+                ["element", name, count] => elements.push(Element {
+                    name: name.to_string(),
+                    count: count.parse().map_err(|_| bad_ply("bad element count"))?,
+                    properties: Vec::new(),
+                }),
+                ["property", "list", count, ty, name] => {
+                    let kind = Kind::List(Type::parse(count)?, Type::parse(ty)?);
+                    add_property(&mut elements, name, kind)?;
+                },
+                ["property", ty, name] => {
+                    let kind = Kind::Scalar(Type::parse(ty)?);
+                    add_property(&mut elements, name, kind)?;
+                },
+                ["end_header"] => break,
+                _ => (), // e.g. comments.
+            }
+        }
+
+        let format = format.ok_or_else(|| bad_ply("missing format"))?;
+        Ok(Self { format, elements })
+    }
+}
+
+fn add_property(elements: &mut [Element], name: &str, kind: Kind) -> Result<(), Error> {
+    let element = elements
+        .last_mut()
+        .ok_or_else(|| bad_ply("property without element"))?;
+    element.properties.push(Property { name: name.to_string(), kind });
+    Ok(())
+}
+
+impl Type {
+    fn parse(s: &str) -> Result<Self, Error> {
+        let ty = match s {
+            "char" | "int8" => Self::I8,
+            "uchar" | "uint8" => Self::U8,
+            "short" | "int16" => Self::I16,
+            "ushort" | "uint16" => Self::U16,
+            "int" | "int32" => Self::I32,
+            "uint" | "uint32" => Self::U32,
+            "float" | "float32" => Self::F32,
+            "double" | "float64" => Self::F64,
+            _ => return Err(bad_ply(&format!("unknown type '{}'", s))),
+        };
+        Ok(ty)
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::I32 | Self::U32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// Map a color component to [0, 1].
+    fn normalise(&self, value: f64) -> f32 {
+        match self {
+            Self::U8 => (value / 255.0) as f32,
+            Self::U16 => (value / 65535.0) as f32,
+            _ => value as f32,
+        }
+    }
+}
+
+
+// ===============================================================================================
+//
+// PLY body.
+//
+// ===============================================================================================
+
+enum Body<R: Read> {
+    Ascii(Vec<String>, usize),
+    Binary(R, bool),
+}
+
+impl<R: Read> Body<R> {
+    fn read(&mut self, ty: Type) -> Result<f64, Error> {
+        match self {
+            Self::Ascii(words, index) => {
+                let word = words
+                    .get(*index)
+                    .ok_or_else(|| bad_ply("unexpected end of data"))?;
+                *index += 1;
+                word.parse().map_err(|_| bad_ply(&format!("bad value '{}'", word)))
+            },
+            Self::Binary(reader, big_endian) => {
+                let mut bytes = [0_u8; 8];
+                let bytes = &mut bytes[..ty.size()];
+                reader.read_exact(bytes)?;
+                if *big_endian {
+                    bytes.reverse();
+                }
+                let value = match ty {
+                    Type::I8 => bytes[0] as i8 as f64,
+                    Type::U8 => bytes[0] as f64,
+                    Type::I16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    Type::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+                    Type::I32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                    Type::U32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                    Type::F32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+                    Type::F64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+                };
+                Ok(value)
+            },
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "\
+        element vertex 4\n\
+        property float x\n\
+        property float y\n\
+        property float z\n\
+        property uchar red\n\
+        property uchar green\n\
+        property uchar blue\n\
+        element face 1\n\
+        property list uchar int vertex_indices\n\
+        end_header\n";
+
+    fn vertices_count(mesh: &Mesh) -> usize {
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().len()
+    }
+
+    #[test]
+    fn ascii() {
+        let data = format!(
+            "ply\nformat ascii 1.0\ncomment a quad\n{}{}",
+            HEADER,
+            "0 0 0 255 0 0\n1 0 0 255 0 0\n1 1 0 255 0 0\n0 1 0 255 0 0\n4 0 1 2 3\n",
+        );
+        let mesh = read(data.as_bytes(), "Quad".to_string()).unwrap();
+        assert_eq!(mesh.name, "Quad");
+        assert_eq!(vertices_count(&mesh.mesh), 6); // Two triangles, with duplicated vertices.
+        let color = mesh.color.unwrap().to_srgba();
+        assert_eq!((color.red, color.green, color.blue), (1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn binary() {
+        for big_endian in [false, true] {
+            let format = if big_endian { "binary_big_endian" } else { "binary_little_endian" };
+            let mut data = format!("ply\nformat {} 1.0\n{}", format, HEADER).into_bytes();
+            let vertices = [[0.0_f32, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+            for vertex in vertices {
+                for value in vertex {
+                    let bytes = if big_endian { value.to_be_bytes() } else { value.to_le_bytes() };
+                    data.extend_from_slice(&bytes);
+                }
+                data.extend_from_slice(&[0, 0, 255]);
+            }
+            data.push(4);
+            for index in [0_i32, 1, 2, 3] {
+                let bytes = if big_endian { index.to_be_bytes() } else { index.to_le_bytes() };
+                data.extend_from_slice(&bytes);
+            }
+            let mesh = read(data.as_slice(), "Quad".to_string()).unwrap();
+            assert_eq!(vertices_count(&mesh.mesh), 6);
+            let color = mesh.color.unwrap().to_srgba();
+            assert_eq!((color.red, color.green, color.blue), (0.0, 0.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn bad_index() {
+        let data = format!(
+            "ply\nformat ascii 1.0\n{}{}",
+            HEADER,
+            "0 0 0 0 0 0\n1 0 0 0 0 0\n1 1 0 0 0 0\n0 1 0 0 0 0\n3 0 1 4\n",
+        );
+        let result = read(data.as_bytes(), "Bad".to_string());
+        assert!(matches!(result, Err(err) if err.kind() == ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn truncated() {
+        let data = format!("ply\nformat ascii 1.0\n{}0 0 0\n", HEADER);
+        assert!(read(data.as_bytes(), "Truncated".to_string()).is_err());
+    }
+}
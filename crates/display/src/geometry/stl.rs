@@ -13,9 +13,12 @@ pub fn load(
     Ok(mesh)
 }
 
+#[derive(Clone, Copy)]
 pub struct LoadSettings {
-    compute_normal: bool,
-    interpolate_normal: bool,
+    /// Recompute face normals from vertices, instead of using the STL ones.
+    pub compute_normal: bool,
+    /// Smooth normals by interpolating them at shared vertices.
+    pub interpolate_normal: bool,
 }
 
 impl Default for LoadSettings {
@@ -6,6 +6,7 @@ pub mod app;
 mod drone;
 mod display;
 pub mod event;
+pub mod export;
 pub mod geometry;
 mod lighting;
 mod ui;
@@ -7,15 +7,20 @@ use bevy::window::PrimaryWindow;
 use crate::app::{AppState, Removable};
 use crate::geometry::GeometrySet;
 
+mod clipping;
 mod event;
+mod filter;
 mod geometry;
+mod legend;
 mod location;
 mod meters;
 mod nord;
 mod scroll;
 mod stats;
+mod summary;
 
-pub use event::UiEvent;
+pub use event::{uformat, UiEvent};
+pub use geometry::UiVolume;
 pub use location::LocationState;
 pub use meters::Meters;
 pub use nord::NORD;
@@ -36,11 +41,15 @@ impl Plugin for UiPlugin {
                     .run_if(in_state(AppState::Display))
                     .after(TextInputSystem)
             );
+        clipping::build(app);
         event::build(app);
+        filter::build(app);
         geometry::build(app);
+        legend::build(app);
         location::build(app);
         scroll::build(app);
         stats::build(app);
+        summary::build(app);
     }
 }
 
@@ -181,6 +190,8 @@ impl UiText {
     const NORMAL: Srgba = NORD[4];
     const HOVERED: Srgba = NORD[7];
     const PRESSED: Srgba = NORD[1];
+    const SELECTED: Srgba = NORD[13];
+    const HIDDEN: Srgba = NORD[10];
 
     #[inline]
     fn font_width() -> f32 {
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use bevy_simple_text_input::{TextInputInactive, TextInputSubmitEvent, TextInputValue};
+use crate::app::AppState;
+use crate::drone::DroneCamera;
+use crate::geometry::{Clipping, RootVolume, Volume, set_clipping};
+use data::geometry::{Normal, Plane};
+use super::{PrimaryMenu, TextInputSet, TextInputState, UiText, UiWindow};
+
+
+pub fn build(app: &mut App) {
+    app
+        .init_state::<ClippingState>()
+        .add_systems(OnEnter(ClippingState::Enabled),
+            setup_panel.run_if(in_state(AppState::Display))
+        )
+        .add_systems(OnExit(ClippingState::Enabled),
+            remove_panel.run_if(in_state(AppState::Display))
+        )
+        .add_systems(OnExit(AppState::Display),
+            disable_panel
+        )
+        .add_systems(Update, (
+            on_keyboard
+                .after(TextInputSet)
+                .run_if(in_state(TextInputState::Inactive))
+                .run_if(in_state(AppState::Display)),
+            on_button
+                .run_if(in_state(ClippingState::Enabled))
+                .run_if(in_state(AppState::Display)),
+            on_submit
+                .run_if(in_state(ClippingState::Enabled))
+                .after(UiText::on_mouse_button),
+            on_clipping
+                .run_if(in_state(ClippingState::Enabled))
+                .run_if(in_state(AppState::Display)),
+        ));
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+pub enum ClippingState {
+    #[default]
+    Disabled,
+    Enabled,
+}
+
+#[derive(Component)]
+struct ClippingPanel;
+
+#[derive(Clone, Copy, Component)]
+enum ClippingButton {
+    Add(Normal),
+    Flip(usize),
+    Remove(usize),
+    Tracks,
+}
+
+/// Offset input of a plane, by index.
+#[derive(Component)]
+struct OffsetInput(usize);
+
+fn on_keyboard(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    current_state: Res<State<ClippingState>>,
+    mut next_state: ResMut<NextState<ClippingState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F10) {
+        match **current_state {
+            ClippingState::Disabled => next_state.set(ClippingState::Enabled),
+            ClippingState::Enabled => next_state.set(ClippingState::Disabled),
+        }
+    }
+}
+
+fn setup_panel(
+    clipping: Res<Clipping>,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+    mut commands: Commands,
+) {
+    spawn_panel(&clipping, primary_menu, &mut commands);
+}
+
+fn remove_panel(
+    panel: Query<Entity, With<ClippingPanel>>,
+    mut commands: Commands,
+) {
+    for panel in panel.iter() {
+        commands.entity(panel).despawn();
+    }
+}
+
+fn disable_panel (mut next_state: ResMut<NextState<ClippingState>>) {
+    next_state.set(ClippingState::Disabled);
+}
+
+/// Rebuild the panel when the clipping planes change, e.g. from Python.
+fn on_clipping(
+    clipping: Res<Clipping>,
+    panel: Query<Entity, With<ClippingPanel>>,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+    mut commands: Commands,
+) {
+    if !clipping.is_changed() {
+        return
+    }
+    for panel in panel.iter() {
+        commands.entity(panel).despawn();
+    }
+    spawn_panel(&clipping, primary_menu, &mut commands);
+}
+
+fn spawn_panel(
+    clipping: &Clipping,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+    commands: &mut Commands,
+) {
+    let Ok(primary_menu) = primary_menu.single() else { return };
+
+    let spawn_row = |commands: &mut Commands, entries: &[Entity]| -> Entity {
+        let mut row = commands.spawn(
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+        );
+        row.add_children(entries);
+        row.id()
+    };
+
+    let mut rows = Vec::new();
+    for (index, plane) in clipping.settings.planes.iter().enumerate() {
+        let label = commands.spawn((
+            UiText::new_bundle(&format_normal(plane)),
+            Node {
+                width: Val::Px((16.0 * UiText::font_width()).round()),
+                margin: UiRect::horizontal(Val::Px(6.0)),
+                ..default()
+            },
+        )).id();
+        let offset = commands.spawn((
+            UiText::new_input(&plane.offset.to_string(), (10.0 * UiText::font_width()).round()),
+            OffsetInput(index),
+        )).id();
+        let unit = commands.spawn(UiText::new_bundle("mm")).id();
+        let flip = UiText::spawn_button(ClippingButton::Flip(index), "flip", commands);
+        let remove = UiText::spawn_button(ClippingButton::Remove(index), "remove", commands);
+        rows.push(spawn_row(commands, &[label, offset, unit, flip, remove]));
+    }
+
+    let add: Vec<Entity> = [
+        ("+x", Normal::Vector([1.0, 0.0, 0.0])),
+        ("+y", Normal::Vector([0.0, 1.0, 0.0])),
+        ("+z", Normal::Vector([0.0, 0.0, 1.0])),
+        ("+camera", Normal::Camera),
+    ]
+        .into_iter()
+        .map(|(label, normal)| UiText::spawn_button(ClippingButton::Add(normal), label, commands))
+        .collect();
+    rows.push(spawn_row(commands, &add));
+
+    let tracks = if clipping.settings.tracks { "tracks: clipped" } else { "tracks: unclipped" };
+    let tracks = UiText::spawn_button(ClippingButton::Tracks, tracks, commands);
+    rows.push(spawn_row(commands, &[tracks]));
+
+    let mut content = commands.spawn(
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+    );
+    content.add_children(&rows);
+    let content = content.id();
+
+    let mut window = UiWindow::new("Clipping", super::WindowLocation::Relative, commands);
+    window.add_child(content);
+    let window = window.id();
+
+    let mut capsule = commands.spawn((
+        ClippingPanel,
+        Node {
+            padding: UiRect::left(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+    capsule.add_child(window);
+    let capsule = capsule.id();
+
+    commands
+        .entity(primary_menu)
+        .add_child(capsule);
+}
+
+fn format_normal(plane: &Plane) -> String {
+    match plane.normal {
+        Normal::Camera => "camera".to_string(),
+        Normal::Vector(vector) => {
+            const AXES: [&str; 3] = ["x", "y", "z"];
+            let axis = vector.iter().position(|x| x.abs() == 1.0);
+            match axis {
+                Some(axis) if vector[axis] > 0.0 => format!("+{}", AXES[axis]),
+                Some(axis) => format!("-{}", AXES[axis]),
+                None => format!("{:.2}, {:.2}, {:.2}", vector[0], vector[1], vector[2]),
+            }
+        },
+    }
+}
+
+fn on_button(
+    interactions: Query<(&Interaction, &ClippingButton, &Children), Changed<Interaction>>,
+    mut text_query: Query<&mut TextColor>,
+    clipping: Res<Clipping>,
+    camera: Query<&GlobalTransform, With<DroneCamera>>,
+    root: Query<&Volume, With<RootVolume>>,
+) {
+    for (interaction, button, children) in interactions.iter() {
+        let mut text_color = text_query.get_mut(children[0]).unwrap();
+        match *interaction {
+            Interaction::Pressed => {
+                let mut settings = clipping.settings.clone();
+                match *button {
+                    ClippingButton::Add(normal) => {
+                        let offset = match normal {
+                            Normal::Camera => camera_offset(&camera, &root),
+                            Normal::Vector(_) => 0.0,
+                        };
+                        settings.planes.push(Plane { normal, offset });
+                    },
+                    ClippingButton::Flip(index) => {
+                        if let Some(plane) = settings.planes.get_mut(index) &&
+                            let Normal::Vector(vector) = plane.normal {
+                            plane.normal = Normal::Vector(vector.map(|x| -x));
+                            plane.offset = -plane.offset;
+                        }
+                    },
+                    ClippingButton::Remove(index) => {
+                        if index < settings.planes.len() {
+                            settings.planes.remove(index);
+                        }
+                    },
+                    ClippingButton::Tracks => settings.tracks = !settings.tracks,
+                }
+                set_clipping(settings);
+                text_color.0 = UiText::PRESSED.into();
+            }
+            Interaction::Hovered => {
+                text_color.0 = UiText::HOVERED.into();
+            }
+            Interaction::None => {
+                text_color.0 = UiText::NORMAL.into();
+            }
+        }
+    }
+}
+
+/// Default offset of camera planes, i.e. the depth of the geometry centre (in mm), such that the
+/// near half of the geometry is cut.
+fn camera_offset(
+    camera: &Query<&GlobalTransform, With<DroneCamera>>,
+    root: &Query<&Volume, With<RootVolume>>,
+) -> f64 {
+    let (Ok(camera), Ok(root)) = (camera.single(), root.single()) else { return 0.0 };
+    let center = Vec3::from(root.aabb.center);
+    let depth = camera.forward().dot(center - camera.translation());
+    (depth.max(0.0) as f64) * 1E+03
+}
+
+fn on_submit(
+    mut events: EventReader<TextInputSubmitEvent>,
+    mut inputs: Query<(&OffsetInput, &mut TextInputInactive, &mut TextInputValue)>,
+    clipping: Res<Clipping>,
+) {
+    for event in events.read() {
+        let Ok((input, mut inactive, mut input_value)) = inputs.get_mut(event.entity)
+            else { continue };
+        inactive.0 = true;
+        let Some(plane) = clipping.settings.planes.get(input.0) else { continue };
+        match event.value.trim().parse::<f64>() {
+            Ok(offset) if offset.is_finite() => {
+                let mut settings = clipping.settings.clone();
+                settings.planes[input.0].offset = offset;
+                set_clipping(settings);
+            },
+            _ => input_value.0 = plane.offset.to_string(),
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use bevy::prelude::*;
+use bevy_simple_text_input::{TextInputInactive, TextInputSubmitEvent, TextInputValue};
 use crate::app::AppState;
 use crate::drone::TargetEvent;
-use crate::event::{Event, EventData, Events, Target, Track, TrackData, Vertex};
+use crate::event::{Event, EventData, Events, Particle, Pick, Target, Track, TrackData, load_event};
 use std::collections::{HashMap, HashSet};
 use super::{PrimaryMenu, Scroll, UiText};
 
@@ -12,7 +13,10 @@ pub fn build(app: &mut App) {
         .add_event::<UpdateEvent>()
         .add_systems(Update, (
             on_button,
-            on_update.after(on_button)
+            on_update.after(on_button),
+            on_submit
+                .after(UiText::on_mouse_button)
+                .before(load_event),
         ).run_if(in_state(AppState::Display)));
 }
 
@@ -26,29 +30,12 @@ impl UiEvent {
     pub fn spawn_info(
         commands: &mut Commands,
         cursor: Vec2,
-        matches: Vec<(&Track, &Vertex)>
+        mut picks: Vec<Pick>,
     ) {
-        struct TrackData<'a> {
-            track: &'a Track,
-            vertices: Vec<&'a Vertex>,
-        }
-
-        let mut tracks: HashMap<i32, TrackData> = HashMap::new();
-        for (track, vertex) in matches.iter() {
-            tracks
-                .entry(track.tid)
-                .and_modify(|data| data.vertices.push(*vertex))
-                .or_insert_with(|| {
-                    let mut vertices = Vec::new();
-                    vertices.push(*vertex);
-                    TrackData { track, vertices }
-                });
-        }
-        let mut tracks: Vec<_> = tracks.values().collect();
-        tracks.sort_by(|a, b| a.track.tid.cmp(&b.track.tid));
+        picks.sort_by_key(|pick| (pick.track.event, pick.track.tid));
 
         let mut windows = Vec::new();
-        for data in tracks.iter() {
+        for data in picks.iter() {
             fn spawn_column<'a, T>(
                 commands: &'a mut Commands,
                 entries: &[T]
@@ -76,6 +63,12 @@ impl UiEvent {
             let mut labels: Vec<&'static str> = Vec::new();
             let mut values: Vec<String> = Vec::new();
 
+            let events: HashSet<usize> = picks.iter().map(|pick| pick.track.event).collect();
+            if events.len() > 1 {
+                labels.push("event");
+                values.push(data.track.event.to_string());
+            }
+
             if data.track.tid > 1 {
                 labels.push("creator");
                 values.push(
@@ -83,16 +76,36 @@ impl UiEvent {
                 );
             };
 
+            let particle = Particle::from_pid(data.track.pid);
+            if let Some(particle) = particle.as_ref() {
+                labels.push("charge");
+                values.push(particle.charge_label());
+                if particle.mass > 0.0 {
+                    labels.push("mass");
+                    values.push(uformat(particle.mass));
+                }
+            }
+
             let n = data.vertices.len();
             let e0 = data.vertices[0].energy;
             let e1 = data.vertices[n - 1].energy;
             if e0 == e1 {
-                labels.push("energy");
+                labels.push("kinetic energy");
                 values.push(uformat(e0));
             } else {
-                labels.push("energies");
+                labels.push("kinetic energies");
                 values.push(format!("{} to {}", uformat(e0), uformat(e1)));
             }
+            if let Some(particle) = particle.filter(|particle| particle.mass > 0.0) {
+                let (e0, e1) = (particle.total_energy(e0), particle.total_energy(e1));
+                if e0 == e1 {
+                    labels.push("total energy");
+                    values.push(uformat(e0));
+                } else {
+                    labels.push("total energies");
+                    values.push(format!("{} to {}", uformat(e0), uformat(e1)));
+                }
+            }
 
             fn dedup(v: &mut Vec<&str>) { // Preserves the initial order.
                 let mut set = HashSet::new();
@@ -139,6 +152,12 @@ impl UiEvent {
                 }
             }
 
+            if let Some(point) = data.point {
+                let r = crate::view_to_world().transform_point3(point);
+                labels.push("closest point");
+                values.push(format!("{:.4}, {:.4}, {:.4} m", r.x, r.y, r.z));
+            }
+
             let labels = spawn_column(commands, &labels);
             let values = spawn_column(commands, &values);
 
@@ -189,7 +208,7 @@ impl UiEvent {
         primary_window: &Window,
         commands: &mut Commands,
     ) {
-        if events.data.0.len() == 0 {
+        if events.data.len() == 0 {
             return
         }
 
@@ -209,13 +228,28 @@ impl UiEvent {
         scroll.add_child(content);
         let scroll = scroll.id();
 
+        let label = commands.spawn(UiText::new_bundle("jump to")).id();
+        let input = commands.spawn((
+            UiText::new_input(&events.index.to_string(), (10.0 * UiText::font_width()).round()),
+            EventInput,
+        )).id();
+        let mut jump = commands.spawn(Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            ..default()
+        });
+        jump.add_children(&[label, input]);
+        let jump = jump.id();
+
         let title = format!("Event [{}]", events.index);
         let mut window = super::UiWindow::new(
             title.as_str(),
             super::WindowLocation::Relative,
             commands
         );
-        window.add_child(scroll);
+        window.add_children(&[jump, scroll]);
         let window = window.id();
 
         let mut capsule = commands.spawn(Node {
@@ -235,6 +269,32 @@ impl UiEvent {
 #[derive(Component)]
 struct EventContent;
 
+#[derive(Component)]
+struct EventInput;
+
+/// Jump to the submitted event id, which is discarded if invalid.
+fn on_submit(
+    mut ev_submit: EventReader<TextInputSubmitEvent>,
+    mut inputs: Query<(&mut TextInputInactive, &mut TextInputValue), With<EventInput>>,
+    mut events: ResMut<Events>,
+) {
+    for submit in ev_submit.read() {
+        let Ok((mut inactive, mut input_value)) = inputs.get_mut(submit.entity)
+            else { continue };
+        inactive.0 = true;
+        // Events are only mutated for a new and existing id, in order to avoid spurious redraws.
+        let index = submit.value
+            .trim()
+            .parse()
+            .ok()
+            .filter(|index| (*index != events.index) && events.contains(*index));
+        match index {
+            Some(index) => events.index = index,
+            None => input_value.0 = events.index.to_string(),
+        }
+    }
+}
+
 fn clear_content(content: Entity, commands: &mut Commands) {
     let mut content = commands.entity(content);
     content.despawn_related::<Children>();
@@ -284,7 +344,7 @@ fn update_content(
         }
     }
 
-    let event = &events.data.0[&events.index];
+    let event = &events.data[&events.index];
     add_button(0, event, &event.tracks[&1], content, expansions, commands);
 }
 
@@ -318,7 +378,7 @@ fn on_button(
         match *interaction {
             Interaction::Pressed => {
                 if keyboard_input.pressed(KeyCode::ShiftLeft) {
-                    let event = &events.data.0[&events.index];
+                    let event = &events.data[&events.index];
                     let track = &event.tracks[&button.0];
                     ev_target.write(TargetEvent(track.target()));
                 } else {
@@ -364,7 +424,7 @@ fn on_update(
                     recurse(expanded, *daughter, event, expansions);
                 }
             }
-            let event = &events.data.0[&events.index];
+            let event = &events.data[&events.index];
             recurse(expansions.0[tid], *tid, event, &mut expansions);
         }
 
@@ -374,7 +434,7 @@ fn on_update(
     }
 }
 
-fn uformat(energy: f32) -> String {
+pub fn uformat(energy: f32) -> String {
     let scale = energy.log10() as i64 + 6;
     if scale <= 2 {
         format!("{:.3} eV", energy * 1E+06)
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use bevy_simple_text_input::{TextInputInactive, TextInputSubmitEvent, TextInputValue};
+use crate::app::AppState;
+use crate::event::Filter;
+use super::{PrimaryMenu, TextInputSet, TextInputState, UiText, UiWindow};
+
+
+pub fn build(app: &mut App) {
+    app
+        .init_state::<FilterState>()
+        .add_systems(OnEnter(FilterState::Enabled),
+            setup_panel.run_if(in_state(AppState::Display))
+        )
+        .add_systems(OnExit(FilterState::Enabled),
+            remove_panel.run_if(in_state(AppState::Display))
+        )
+        .add_systems(OnExit(AppState::Display),
+            disable_panel
+        )
+        .add_systems(Update, (
+            on_keyboard
+                .after(TextInputSet)
+                .run_if(in_state(TextInputState::Inactive))
+                .run_if(in_state(AppState::Display)),
+            on_submit
+                .run_if(in_state(FilterState::Enabled))
+                .after(UiText::on_mouse_button),
+            on_filter
+                .run_if(in_state(FilterState::Enabled))
+                .after(on_submit),
+        ));
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+pub enum FilterState {
+    #[default]
+    Disabled,
+    Enabled,
+}
+
+#[derive(Component)]
+struct FilterPanel;
+
+#[derive(Clone, Copy, Component)]
+enum Property {
+    Pids,
+    EnergyMin,
+    EnergyMax,
+    Creators,
+    Processes,
+    Volumes,
+}
+
+impl Property {
+    const ALL: [Self; 6] = [
+        Self::Pids, Self::EnergyMin, Self::EnergyMax, Self::Creators, Self::Processes,
+        Self::Volumes,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Pids => "hidden pids",
+            Self::EnergyMin => "min energy",
+            Self::EnergyMax => "max energy",
+            Self::Creators => "hidden creators",
+            Self::Processes => "hidden processes",
+            Self::Volumes => "hidden volumes",
+        }
+    }
+
+    fn unit(&self) -> &'static str {
+        match self {
+            Self::EnergyMin | Self::EnergyMax => "MeV",
+            _ => "",
+        }
+    }
+}
+
+fn on_keyboard(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    current_state: Res<State<FilterState>>,
+    mut next_state: ResMut<NextState<FilterState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        match **current_state {
+            FilterState::Disabled => next_state.set(FilterState::Enabled),
+            FilterState::Enabled => next_state.set(FilterState::Disabled),
+        }
+    }
+}
+
+fn setup_panel(
+    mut commands: Commands,
+    filter: Res<Filter>,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+) -> Result<()> {
+    let labels = Property::ALL.map(|property| {
+        commands.spawn(UiText::new_bundle(property.label())).id()
+    });
+    let values = Property::ALL.map(|property| {
+        let value = property.format(&filter);
+        commands.spawn((
+            UiText::new_input(&value, (20.0 * UiText::font_width()).round()),
+            property,
+        )).id()
+    });
+    let units = Property::ALL.map(|property| {
+        commands.spawn(UiText::new_bundle(property.unit())).id()
+    });
+
+    let columns = [labels, values, units];
+    let columns = columns.map(|column| {
+        let mut entity = commands.spawn(
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            }
+        );
+        entity.add_children(&column);
+        entity.id()
+    });
+
+    let mut content = commands.spawn(
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+    );
+    content.add_children(&columns);
+    let content = content.id();
+
+    let mut window = UiWindow::new("Filter", super::WindowLocation::Relative, &mut commands);
+    window.add_child(content);
+    let window = window.id();
+
+    let mut capsule = commands.spawn((
+        FilterPanel,
+        Node {
+            padding: UiRect::left(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+    capsule.add_child(window);
+    let capsule = capsule.id();
+
+    commands
+        .entity(primary_menu.single()?)
+        .add_child(capsule);
+    Ok(())
+}
+
+fn remove_panel(
+    panel: Query<Entity, With<FilterPanel>>,
+    mut commands: Commands,
+) {
+    if let Ok(panel) = panel.single() {
+        commands.entity(panel).despawn();
+    }
+}
+
+fn disable_panel (mut next_state: ResMut<NextState<FilterState>>) {
+    next_state.set(FilterState::Disabled);
+}
+
+fn on_submit(
+    mut events: EventReader<TextInputSubmitEvent>,
+    mut inputs: Query<(&Property, &mut TextInputInactive, &mut TextInputValue)>,
+    mut filter: ResMut<Filter>,
+) {
+    for event in events.read() {
+        let Ok((property, mut inactive, mut input_value)) = inputs.get_mut(event.entity)
+            else { continue };
+        inactive.0 = true;
+        property.update(&event.value, &mut filter);
+        input_value.0 = property.format(&filter);
+    }
+}
+
+/// Refresh the panel, e.g. if the filter was set from Python.
+fn on_filter(
+    filter: Res<Filter>,
+    mut inputs: Query<(&Property, &TextInputInactive, &mut TextInputValue)>,
+) {
+    if !filter.is_changed() {
+        return
+    }
+    for (property, inactive, mut input_value) in inputs.iter_mut() {
+        if inactive.0 {
+            let value = property.format(&filter);
+            if input_value.0 != value {
+                input_value.0 = value;
+            }
+        }
+    }
+}
+
+impl Property {
+    /// Update the filter from an input value, which is discarded if invalid.
+    fn update(&self, new_value: &str, filter: &mut Filter) {
+        match self {
+            Self::Pids => {
+                let pids: Option<Vec<i32>> = split(new_value)
+                    .map(|pid| pid.parse().ok())
+                    .collect();
+                if let Some(pids) = pids {
+                    filter.pids = pids;
+                }
+            },
+            Self::EnergyMin => {
+                if let Some(energy) = parse_energy(new_value) &&
+                    is_ordered(energy, filter.energy_max) {
+                    filter.energy_min = energy;
+                }
+            },
+            Self::EnergyMax => {
+                if let Some(energy) = parse_energy(new_value) &&
+                    is_ordered(filter.energy_min, energy) {
+                    filter.energy_max = energy;
+                }
+            },
+            Self::Creators => filter.creators = split(new_value).map(str::to_owned).collect(),
+            Self::Processes => filter.processes = split(new_value).map(str::to_owned).collect(),
+            Self::Volumes => filter.volumes = split(new_value).map(str::to_owned).collect(),
+        }
+    }
+
+    fn format(&self, filter: &Filter) -> String {
+        fn join<T: ToString>(values: &[T]) -> String {
+            values
+                .iter()
+                .map(T::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        match self {
+            Self::Pids => join(&filter.pids),
+            Self::EnergyMin => filter.energy_min.map(|e| e.to_string()).unwrap_or_default(),
+            Self::EnergyMax => filter.energy_max.map(|e| e.to_string()).unwrap_or_default(),
+            Self::Creators => join(&filter.creators),
+            Self::Processes => join(&filter.processes),
+            Self::Volumes => join(&filter.volumes),
+        }
+    }
+}
+
+/// Split a comma (or space) separated list of values.
+fn split(value: &str) -> impl Iterator<Item=&str> {
+    value
+        .split(|c: char| (c == ',') || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+}
+
+/// Check that energy bounds are ordered, as required by Python's `filter`.
+fn is_ordered(min: Option<f32>, max: Option<f32>) -> bool {
+    match (min, max) {
+        (Some(min), Some(max)) => min <= max,
+        _ => true,
+    }
+}
+
+/// Parse an optional energy, returning `None` if invalid.
+fn parse_energy(value: &str) -> Option<Option<f32>> {
+    let value = value.trim();
+    if value.is_empty() {
+        Some(None)
+    } else {
+        value.parse().ok().map(Some)
+    }
+}
@@ -2,24 +2,36 @@ use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use crate::app::AppState;
 use crate::drone::TargetEvent;
-use crate::geometry::{RootVolume, Volume};
-use super::{PrimaryMenu, Scroll, UiText, UiWindow, WindowLocation};
+use crate::geometry::{Properties, RootVolume, Selection, Volume};
+use super::{PrimaryMenu, Scroll, UiRoot, UiText, UiWindow, WindowLocation};
 
 
 pub fn build(app: &mut App) {
     app
         .add_event::<UpdateEvent>()
+        .add_event::<VisibilityEvent>()
         .add_systems(OnEnter(AppState::Display), setup_window.after(PrimaryMenu::spawn))
         .add_systems(Update, (
             on_button,
-            on_update.after(on_button)
-
+            on_visibility_button,
+            on_update.after(on_button),
+            on_visibility.after(on_button).after(on_visibility_button),
+            on_selection,
         ).run_if(in_state(AppState::Display)));
 }
 
 #[derive(Component)]
 struct VolumeContent;
 
+/// Visibility actions, on a volume row or on the whole tree.
+#[derive(Clone, Copy, Component)]
+enum VisibilityButton {
+    Toggle(Entity),
+    ToggleDaughters(Entity),
+    Isolate(Entity),
+    ShowAll,
+}
+
 pub fn setup_window(
     mut commands: Commands,
     root: Query<Entity, With<RootVolume>>,
@@ -27,6 +39,7 @@ pub fn setup_window(
     primary_window: Query<&Window, With<PrimaryWindow>>,
     children: Query<&Children, With<Volume>>,
     volumes: Query<&Volume>,
+    selection: Res<Selection>,
 ) -> Result<()> {
     let Ok(primary_window) = primary_window.single() else { return Ok(()) };
 
@@ -43,8 +56,10 @@ pub fn setup_window(
     scroll.add_child(content);
     let scroll = scroll.id();
 
+    let show_all = UiText::spawn_button(VisibilityButton::ShowAll, "show all", &mut commands);
+
     let mut window = UiWindow::new("Volumes", WindowLocation::Relative, &mut commands);
-    window.add_child(scroll);
+    window.add_children(&[show_all, scroll]);
     let window = window.id();
 
     commands
@@ -57,25 +72,48 @@ pub fn setup_window(
         &root,
         &children,
         &volumes,
+        &selection,
     )
 }
 
 #[derive(Event)]
 struct UpdateEvent(Entity, bool);
 
+#[derive(Event)]
+enum VisibilityEvent {
+    /// Show or hide a volume, but not its daughters.
+    Toggle(Entity),
+    /// Show or hide all the daughters of a volume, recursively.
+    ToggleDaughters(Entity),
+    /// Show only a volume and its daughters.
+    Isolate(Entity),
+    ShowAll,
+}
+
 fn on_button(
     interactions: Query<(&Interaction, &VolumeButton, &Children), Changed<Interaction>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut text_query: Query<&mut TextColor>,
     volumes: Query<&Volume>,
+    selection: Res<Selection>,
     mut ev_target: EventWriter<TargetEvent>,
     mut ev_update: EventWriter<UpdateEvent>,
+    mut ev_visibility: EventWriter<VisibilityEvent>,
 ) {
     for (interaction, button, children) in interactions.iter() {
         let mut text_color = text_query.get_mut(children[0]).unwrap();
         match *interaction {
             Interaction::Pressed => {
-                if keyboard_input.pressed(KeyCode::ShiftLeft) {
+                if keyboard_input.pressed(KeyCode::AltLeft) {
+                    let event = if keyboard_input.pressed(KeyCode::ShiftLeft) {
+                        VisibilityEvent::Isolate(button.0)
+                    } else if keyboard_input.pressed(KeyCode::ControlLeft) {
+                        VisibilityEvent::ToggleDaughters(button.0)
+                    } else {
+                        VisibilityEvent::Toggle(button.0)
+                    };
+                    ev_visibility.write(event);
+                } else if keyboard_input.pressed(KeyCode::ShiftLeft) {
                     let volume = volumes.get(button.0).unwrap();
                     ev_target.write(TargetEvent(volume.target()));
                 } else {
@@ -87,6 +125,37 @@ fn on_button(
             Interaction::Hovered => {
                 text_color.0 = UiText::HOVERED.into();
             }
+            Interaction::None => {
+                let volume = volumes.get(button.0).unwrap();
+                text_color.0 = button.color(volume, &selection).into();
+            }
+        }
+    }
+}
+
+fn on_visibility_button(
+    interactions: Query<(&Interaction, &VisibilityButton, &Children), Changed<Interaction>>,
+    mut text_query: Query<&mut TextColor>,
+    mut ev_visibility: EventWriter<VisibilityEvent>,
+) {
+    for (interaction, button, children) in interactions.iter() {
+        let mut text_color = text_query.get_mut(children[0]).unwrap();
+        match *interaction {
+            Interaction::Pressed => {
+                let event = match *button {
+                    VisibilityButton::Toggle(entity) => VisibilityEvent::Toggle(entity),
+                    VisibilityButton::ToggleDaughters(entity) => {
+                        VisibilityEvent::ToggleDaughters(entity)
+                    },
+                    VisibilityButton::Isolate(entity) => VisibilityEvent::Isolate(entity),
+                    VisibilityButton::ShowAll => VisibilityEvent::ShowAll,
+                };
+                ev_visibility.write(event);
+                text_color.0 = UiText::PRESSED.into();
+            }
+            Interaction::Hovered => {
+                text_color.0 = UiText::HOVERED.into();
+            }
             Interaction::None => {
                 text_color.0 = UiText::NORMAL.into();
             }
@@ -101,6 +170,7 @@ fn on_update(
     root: Query<Entity, With<RootVolume>>,
     children: Query<&Children, With<Volume>>,
     mut volumes: Query<&mut Volume>,
+    selection: Res<Selection>,
 ) -> Result<()> {
     for event in events.read() {
         let mut volume = volumes.get_mut(event.0)?;
@@ -128,17 +198,101 @@ fn on_update(
             &root,
             &children,
             &volumes.as_readonly(),
+            &selection,
+        )?;
+    }
+    Ok(())
+}
+
+fn on_visibility(
+    mut commands: Commands,
+    mut events: EventReader<VisibilityEvent>,
+    menu: Query<Entity, With<VolumeContent>>,
+    root: Query<Entity, With<RootVolume>>,
+    children: Query<&Children, With<Volume>>,
+    mut volumes: Query<&mut Volume>,
+    selection: Res<Selection>,
+) -> Result<()> {
+    for event in events.read() {
+        match event {
+            VisibilityEvent::Toggle(entity) => {
+                let mut volume = volumes.get_mut(*entity)?;
+                volume.visible = !volume.visible;
+            },
+            VisibilityEvent::ToggleDaughters(entity) => {
+                let daughters: Vec<Entity> = children.iter_descendants(*entity).collect();
+                let visible = !daughters
+                    .iter()
+                    .any(|daughter| volumes.get(*daughter).is_ok_and(|volume| volume.visible));
+                for daughter in daughters {
+                    volumes.get_mut(daughter)?.visible = visible;
+                }
+            },
+            VisibilityEvent::Isolate(entity) => {
+                for mut volume in volumes.iter_mut() {
+                    volume.visible = false;
+                }
+                volumes.get_mut(*entity)?.visible = true;
+                for daughter in children.iter_descendants(*entity) {
+                    volumes.get_mut(daughter)?.visible = true;
+                }
+            },
+            VisibilityEvent::ShowAll => {
+                for mut volume in volumes.iter_mut() {
+                    volume.visible = true;
+                }
+            },
+        }
+        update_window(
+            menu.single()?,
+            &mut commands,
+            &root,
+            &children,
+            &volumes.as_readonly(),
+            &selection,
         )?;
     }
     Ok(())
 }
 
+/// Reveal and highlight the picked volume in the tree.
+fn on_selection(
+    mut commands: Commands,
+    selection: Res<Selection>,
+    menu: Query<Entity, With<VolumeContent>>,
+    root: Query<Entity, With<RootVolume>>,
+    children: Query<&Children, With<Volume>>,
+    parents: Query<&ChildOf, With<Volume>>,
+    mut volumes: Query<&mut Volume>,
+) -> Result<()> {
+    if !selection.is_changed() {
+        return Ok(())
+    }
+    let Ok(menu) = menu.single() else { return Ok(()) };
+    if let Some(entity) = selection.0 {
+        for ancestor in parents.iter_ancestors(entity) {
+            if let Ok(mut volume) = volumes.get_mut(ancestor) {
+                volume.expanded = true;
+            }
+        }
+    }
+    update_window(
+        menu,
+        &mut commands,
+        &root,
+        &children,
+        &volumes.as_readonly(),
+        &selection,
+    )
+}
+
 fn update_window(
     content: Entity,
     commands: &mut Commands,
     root: &Query<Entity, With<RootVolume>>,
     children: &Query<&Children, With<Volume>>,
     volumes: &Query<&Volume>,
+    selection: &Selection,
 ) -> Result<()> {
     fn add_button(
         depth: usize,
@@ -147,6 +301,7 @@ fn update_window(
         commands: &mut Commands,
         children: &Query<&Children, With<Volume>>,
         volumes: &Query<&Volume>,
+        selection: &Selection,
     ) -> Result<()> {
         let volume = volumes.get(entity)?;
         let childs = children.get(entity).ok();
@@ -156,14 +311,38 @@ fn update_window(
             ""
         };
         let label = format!("{}{}{}", "  ".repeat(depth), volume.name, qualifier);
-        let button = VolumeButton::spawn_button(label.as_str(), entity, commands);
+        let button = VolumeButton::spawn_button(
+            label.as_str(), entity, volume, selection, commands,
+        );
+
+        // Visibility actions. The daughters toggle is only shown for volumes with daughters.
+        let mut entries = vec![button];
+        if childs.is_some() {
+            entries.push(VisibilityButton::ToggleDaughters(entity).spawn("daughters", commands));
+        }
+        entries.push(VisibilityButton::Isolate(entity).spawn("isolate", commands));
+        let toggle = if volume.visible { "hide" } else { "show" };
+        entries.push(VisibilityButton::Toggle(entity).spawn(toggle, commands));
+
+        let mut row = commands.spawn(
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+        );
+        row.add_children(&entries);
+        let row = row.id();
         commands
             .entity(content)
-            .add_child(button);
+            .add_child(row);
         if volume.expanded {
             if let Some(childs) = childs {
                 for child in childs {
-                    add_button(depth + 1, *child, content, commands, children, volumes)?;
+                    add_button(
+                        depth + 1, *child, content, commands, children, volumes, selection,
+                    )?;
                 }
             }
         }
@@ -171,7 +350,7 @@ fn update_window(
     }
 
     clear_window(content, commands);
-    add_button(0, root.single()?, content, commands, children, volumes)
+    add_button(0, root.single()?, content, commands, children, volumes, selection)
 }
 
 fn clear_window(content: Entity, commands: &mut Commands) {
@@ -185,10 +364,126 @@ pub struct VolumeButton(Entity);
 impl VolumeButton {
     fn spawn_button(
         message: &str,
-        volume: Entity,
+        entity: Entity,
+        volume: &Volume,
+        selection: &Selection,
         commands: &mut Commands,
     ) -> Entity {
-        let component = VolumeButton(volume);
-        UiText::spawn_button(component, message, commands)
+        let component = VolumeButton(entity);
+        let color = component.color(volume, selection);
+        commands.spawn((
+            component,
+            Button,
+            Node {
+                margin: UiRect::vertical(Val::Px(2.0)),
+                flex_grow: 1.0,
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(UiText::new_bundle(message))
+                .insert(TextColor(color.into()));
+        })
+        .id()
+    }
+
+    fn color(&self, volume: &Volume, selection: &Selection) -> Srgba {
+        if selection.0 == Some(self.0) {
+            UiText::SELECTED
+        } else if !volume.visible {
+            UiText::HIDDEN
+        } else {
+            UiText::NORMAL
+        }
+    }
+}
+
+impl VisibilityButton {
+    fn spawn(self, message: &str, commands: &mut Commands) -> Entity {
+        let button = UiText::spawn_button(self, message, commands);
+        commands
+            .entity(button)
+            .insert(Node {
+                margin: UiRect::new(Val::Px(8.0), Val::Px(0.0), Val::Px(2.0), Val::Px(2.0)),
+                ..default()
+            });
+        button
+    }
+}
+
+#[derive(Component)]
+pub struct UiVolume;
+
+impl UiVolume {
+    pub fn spawn_info(
+        commands: &mut Commands,
+        cursor: Vec2,
+        name: &str,
+        path: &str,
+        properties: Option<&Properties>,
+    ) {
+        let mut labels: Vec<String> = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+
+        labels.push("path".to_string());
+        values.push(path.to_string());
+
+        if let Some(properties) = properties {
+            labels.push("solid".to_string());
+            values.push(properties.solid.to_string());
+            for (label, value) in properties.parameters.iter() {
+                labels.push(format!("  {}", label));
+                values.push(value.clone());
+            }
+            labels.push("material".to_string());
+            values.push(properties.material.clone());
+            labels.push("density".to_string());
+            values.push(format!("{} g/cm3", properties.density));
+            labels.push("state".to_string());
+            values.push(properties.state.clone());
+            labels.push("composition".to_string());
+            values.push(properties.composition
+                .iter()
+                .map(|(element, fraction)| format!("{} {:.1}%", element, 100.0 * fraction))
+                .collect::<Vec<_>>()
+                .join(", ")
+            );
+        }
+
+        let columns = [labels, values].map(|column| {
+            let entries: Vec<Entity> = column
+                .iter()
+                .map(|entry| commands.spawn(UiText::new_bundle(entry)).id())
+                .collect();
+            let mut entity = commands.spawn(
+                Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                }
+            );
+            entity.add_children(&entries);
+            entity.id()
+        });
+
+        let mut content = commands.spawn(
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+        );
+        content.add_children(&columns);
+        let content = content.id();
+
+        let mut window = UiWindow::new(name, WindowLocation::Cursor(cursor), commands);
+        window.add_child(content);
+        let window = window.id();
+
+        commands
+            .entity(window)
+            .insert((UiVolume, UiRoot));
     }
 }
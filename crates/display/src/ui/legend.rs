@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use crate::app::AppState;
+use crate::event::Legend;
+use super::{PrimaryMenu, UiText, UiWindow};
+
+
+pub fn build(app: &mut App) {
+    app.add_systems(Update, update_panel.run_if(in_state(AppState::Display)));
+}
+
+#[derive(Component)]
+struct LegendPanel;
+
+/// Respawn the legend panel when the tracks coloring changes.
+fn update_panel(
+    legend: Res<Legend>,
+    panel: Query<Entity, With<LegendPanel>>,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+    mut commands: Commands,
+) {
+    if !legend.is_changed() {
+        return
+    }
+    for entity in panel.iter() {
+        commands.entity(entity).despawn();
+    }
+    let Ok(primary_menu) = primary_menu.single() else { return };
+    if legend.entries.is_empty() {
+        return
+    }
+
+    const SWATCH_SIZE: f32 = 10.0;
+
+    let entries: Vec<Entity> = legend.entries
+        .iter()
+        .map(|(label, colour)| {
+            let swatch = commands.spawn((
+                Node {
+                    width: Val::Px(SWATCH_SIZE),
+                    height: Val::Px(SWATCH_SIZE),
+                    margin: UiRect::left(Val::Px(6.0)),
+                    ..default()
+                },
+                BackgroundColor((*colour).into()),
+            )).id();
+            let label = commands.spawn(UiText::new_bundle(label)).id();
+            let mut entry = commands.spawn(Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..default()
+            });
+            entry.add_children(&[swatch, label]);
+            entry.id()
+        })
+        .collect();
+
+    let mut content = commands.spawn(Node {
+        display: Display::Flex,
+        flex_direction: FlexDirection::Column,
+        padding: UiRect::all(Val::Px(4.0)),
+        ..default()
+    });
+    content.add_children(&entries);
+    let content = content.id();
+
+    let mut window = UiWindow::new(
+        legend.title.as_str(),
+        super::WindowLocation::Relative,
+        &mut commands,
+    );
+    window.add_child(content);
+    let window = window.id();
+
+    let mut capsule = commands.spawn((
+        LegendPanel,
+        Node {
+            padding: UiRect::left(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+    capsule.add_child(window);
+    let capsule = capsule.id();
+
+    commands
+        .entity(primary_menu)
+        .add_child(capsule);
+}
@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+use crate::app::AppState;
+use crate::event::{load_event, EventData, Events, Particle};
+use std::collections::{BTreeMap, HashMap};
+use super::{PrimaryMenu, TextInputSet, TextInputState, UiText, UiWindow, uformat};
+
+
+pub fn build(app: &mut App) {
+    app
+        .init_state::<SummaryState>()
+        .add_systems(OnEnter(SummaryState::Enabled),
+            setup_panel.run_if(in_state(AppState::Display))
+        )
+        .add_systems(OnExit(SummaryState::Enabled),
+            remove_panel.run_if(in_state(AppState::Display))
+        )
+        .add_systems(OnExit(AppState::Display),
+            disable_panel
+        )
+        .add_systems(Update, (
+            on_keyboard
+                .after(TextInputSet)
+                .run_if(in_state(TextInputState::Inactive))
+                .run_if(in_state(AppState::Display)),
+            on_events
+                .after(load_event)
+                .run_if(in_state(SummaryState::Enabled))
+                .run_if(in_state(AppState::Display)),
+        ));
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+pub enum SummaryState {
+    #[default]
+    Disabled,
+    Enabled,
+}
+
+#[derive(Component)]
+struct SummaryPanel;
+
+fn on_keyboard(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    current_state: Res<State<SummaryState>>,
+    mut next_state: ResMut<NextState<SummaryState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F7) {
+        match **current_state {
+            SummaryState::Disabled => next_state.set(SummaryState::Enabled),
+            SummaryState::Enabled => next_state.set(SummaryState::Disabled),
+        }
+    }
+}
+
+fn setup_panel(
+    events: Res<Events>,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+    mut commands: Commands,
+) {
+    spawn_panel(&events, primary_menu, &mut commands);
+}
+
+fn remove_panel(
+    panel: Query<Entity, With<SummaryPanel>>,
+    mut commands: Commands,
+) {
+    for panel in panel.iter() {
+        commands.entity(panel).despawn();
+    }
+}
+
+fn disable_panel (mut next_state: ResMut<NextState<SummaryState>>) {
+    next_state.set(SummaryState::Disabled);
+}
+
+/// Rebuild the panel when the current event changes.
+fn on_events(
+    events: Res<Events>,
+    panel: Query<Entity, With<SummaryPanel>>,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+    mut commands: Commands,
+) {
+    if !events.is_changed() {
+        return
+    }
+    for panel in panel.iter() {
+        commands.entity(panel).despawn();
+    }
+    spawn_panel(&events, primary_menu, &mut commands);
+}
+
+fn spawn_panel(
+    events: &Events,
+    primary_menu: Query<Entity, With<PrimaryMenu>>,
+    commands: &mut Commands,
+) {
+    let Ok(primary_menu) = primary_menu.single() else { return };
+    let Some(event) = events.data.get(&events.index) else { return };
+    let summary = Summary::new(event);
+
+    let mut labels: Vec<String> = Vec::new();
+    let mut values: Vec<String> = Vec::new();
+
+    labels.push("tracks".to_string());
+    values.push(summary.tracks.to_string());
+    let mut species: Vec<_> = summary.species.iter().collect();
+    species.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    for (pid, count) in species {
+        labels.push(format!("  {}", Particle::name(*pid)));
+        values.push(count.to_string());
+    }
+
+    labels.push("vertices".to_string());
+    values.push(summary.vertices.to_string());
+
+    labels.push("primary energy".to_string());
+    values.push(uformat(summary.primary_energy));
+
+    if !summary.deposits.is_empty() {
+        labels.push("visible energy".to_string());
+        values.push(uformat(summary.deposits.values().sum()));
+        for (volume, energy) in summary.deposits.iter() {
+            labels.push(format!("  {}", volume));
+            values.push(uformat(*energy));
+        }
+    }
+
+    if !summary.processes.is_empty() {
+        labels.push("processes".to_string());
+        values.push(String::new());
+        for (process, count) in summary.processes.iter() {
+            labels.push(format!("  {}", process));
+            values.push(count.to_string());
+        }
+    }
+
+    let columns = [labels, values].map(|column| {
+        let entries: Vec<Entity> = column
+            .iter()
+            .map(|entry| commands.spawn(UiText::new_bundle(entry)).id())
+            .collect();
+        let mut entity = commands.spawn(
+            Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            }
+        );
+        entity.add_children(&entries);
+        entity.id()
+    });
+
+    let mut content = commands.spawn(
+        Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Row,
+            ..default()
+        },
+    );
+    content.add_children(&columns);
+    let content = content.id();
+
+    let title = format!("Summary [{}]", events.index);
+    let mut window = UiWindow::new(
+        title.as_str(),
+        super::WindowLocation::Relative,
+        commands,
+    );
+    window.add_child(content);
+    let window = window.id();
+
+    let mut capsule = commands.spawn((
+        SummaryPanel,
+        Node {
+            padding: UiRect::left(Val::Px(4.0)),
+            ..default()
+        },
+    ));
+    capsule.add_child(window);
+    let capsule = capsule.id();
+
+    commands
+        .entity(primary_menu)
+        .add_child(capsule);
+}
+
+
+// ===============================================================================================
+//
+// Event summary.
+//
+// ===============================================================================================
+
+struct Summary<'a> {
+    tracks: usize,
+    species: HashMap<i32, usize>,
+    vertices: usize,
+    /// Total kinetic energy of primary tracks, in MeV.
+    primary_energy: f32,
+    /// Visible energy per volume, in MeV.
+    deposits: BTreeMap<&'a str, f32>,
+    /// Vertices count per process.
+    processes: BTreeMap<&'a str, usize>,
+}
+
+impl<'a> Summary<'a> {
+    /// Summarise an event. The visible energy is estimated as the kinetic energy lost by charged
+    /// tracks between successive vertices, minus the energy carried away by their daughters.
+    fn new(event: &'a EventData) -> Self {
+        let mut species = HashMap::new();
+        let mut vertices = 0;
+        let mut primary_energy = 0.0;
+        let mut deposits = BTreeMap::new();
+        let mut processes = BTreeMap::new();
+        for track in event.tracks.values() {
+            *species.entry(track.pid).or_insert(0) += 1;
+            vertices += track.vertices.len();
+            for vertex in track.vertices.iter() {
+                if !vertex.process.is_empty() {
+                    *processes.entry(vertex.process.as_str()).or_insert(0) += 1;
+                }
+            }
+            if !event.tracks.contains_key(&track.parent) {
+                primary_energy += track.vertices
+                    .first()
+                    .map(|vertex| vertex.energy)
+                    .unwrap_or(0.0);
+            }
+
+            let charged = Particle::from_pid(track.pid)
+                .map(|particle| particle.charge != 0.0)
+                .unwrap_or(false);
+            if !charged {
+                continue
+            }
+            for step in track.vertices.windows(2) {
+                let loss = step[0].energy - step[1].energy;
+                if (loss > 0.0) && !step[0].volume.is_empty() {
+                    *deposits.entry(step[0].volume.as_str()).or_insert(0.0) += loss;
+                }
+            }
+            for daughter in track.daughters.iter() {
+                let Some(start) = event.tracks
+                    .get(daughter)
+                    .and_then(|daughter| daughter.vertices.first()) else { continue };
+                if !start.volume.is_empty() {
+                    *deposits.entry(start.volume.as_str()).or_insert(0.0) -= start.energy;
+                }
+            }
+        }
+        deposits.retain(|_, deposit| *deposit > 0.0);
+
+        Self {
+            tracks: event.tracks.len(),
+            species,
+            vertices,
+            primary_energy,
+            deposits,
+            processes,
+        }
+    }
+}